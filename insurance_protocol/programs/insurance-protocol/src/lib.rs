@@ -1,94 +1,634 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount};  // Fixed import
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
 
 declare_id!("3bv8Hi7JYzuTdPJxMut67P7qRoZdJrJM33pwKZFp82tz");
 
+const BPS_DENOMINATOR: u64 = 10_000;
+const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+// Utilization above which the premium multiplier rises steeply
+const UTILIZATION_KINK_BPS: u64 = 8_000;
+const MULTIPLIER_AT_KINK_BPS: u64 = 15_000;
+const MULTIPLIER_AT_FULL_BPS: u64 = 45_000;
+// Pause flags; each bit halts one group of instructions
+const PAUSE_NEW_POLICIES: u8 = 1 << 0;
+const PAUSE_STAKING: u8 = 1 << 1;
+const PAUSE_UNSTAKING: u8 = 1 << 2;
+const PAUSE_CLAIM_SUBMISSION: u8 = 1 << 3;
+const PAUSE_CLAIM_PAYOUTS: u8 = 1 << 4;
+const PAUSE_TREASURY: u8 = 1 << 5;
+const PAUSE_ALL: u8 = (1 << 6) - 1;
+// Furthest in the future a purchased policy may be scheduled to start
+const MAX_START_DELAY: i64 = 90 * 24 * 60 * 60;
+// Shares locked in the escrow on the first stake so donations cannot inflate the share price
+const MINIMUM_LIQUIDITY: u64 = 1_000;
+
 #[program]
 pub mod insurance_protocol {
     use super::*;
 
-    // Initialize the insurance pool by admin
-    pub fn initialize_pool(ctx: Context<InitializePool>, bump: u8) -> Result<()> {
+    // Initialize the insurance pool and its token vault by admin
+    pub fn initialize_pool(
+        ctx: Context<InitializePool>,
+        unstake_cooldown: i64,
+        min_policy_term: i64,
+        max_policy_term: i64,
+        grace_period: i64,
+        withdrawal_delay: i64,
+    ) -> Result<()> {
+        require!(unstake_cooldown >= 0, InsuranceError::InvalidDuration);
+        require!(grace_period >= 0, InsuranceError::InvalidDuration);
+        require!(withdrawal_delay >= 0, InsuranceError::InvalidDuration);
+        require!(
+            min_policy_term > 0 && min_policy_term <= max_policy_term,
+            InsuranceError::InvalidDuration
+        );
+
         let pool = &mut ctx.accounts.insurance_pool;
         pool.total_premium_collected = 0;
         pool.total_claims_paid = 0;
-        pool.authority = *ctx.accounts.pool_authority.key;
+        pool.authority = ctx.accounts.admin.key();
+        pool.claims_assessor = ctx.accounts.admin.key();
+        pool.treasurer = ctx.accounts.admin.key();
+        pool.pauser = ctx.accounts.admin.key();
+        pool.pending_authority = Pubkey::default();
+        pool.pause_flags = 0;
+        pool.multisig_signer = Pubkey::default();
+        pool.large_claim_threshold = 0;
+        pool.mint = ctx.accounts.mint.key();
+        pool.vault = ctx.accounts.vault.key();
+        pool.lp_mint = ctx.accounts.lp_mint.key();
+        pool.share_escrow = ctx.accounts.share_escrow.key();
+        pool.pool_authority_bump = ctx.bumps.pool_authority;
+        pool.vault_bump = ctx.bumps.vault;
+        pool.lp_mint_bump = ctx.bumps.lp_mint;
+        pool.active_coverage = 0;
+        pool.unstake_cooldown = unstake_cooldown;
+        pool.next_unstake_ticket = 0;
+        pool.unstake_queue_head = 0;
+        pool.pending_unstake_shares = 0;
+        pool.min_policy_term = min_policy_term;
+        pool.max_policy_term = max_policy_term;
+        pool.product_count = 0;
+        pool.grace_period = grace_period;
+        pool.withdrawal_delay = withdrawal_delay;
+        pool.next_withdrawal_id = 0;
+        Ok(())
+    }
+
+    // Admin hands a role to a new key; the previous holder loses it
+    pub fn grant_role(ctx: Context<ManageRole>, role: Role, grantee: Pubkey) -> Result<()> {
+        require!(grantee != Pubkey::default(), InsuranceError::InvalidRoleHolder);
+        ctx.accounts.insurance_pool.set_role(role, grantee);
+        Ok(())
+    }
+
+    // Admin revokes a role; its instructions are unusable until it is granted again
+    pub fn revoke_role(ctx: Context<ManageRole>, role: Role) -> Result<()> {
+        ctx.accounts.insurance_pool.set_role(role, Pubkey::default());
+        Ok(())
+    }
+
+    // Admin nominates a new admin; nothing changes until the nominee accepts
+    pub fn propose_authority_transfer(ctx: Context<ManageRole>, new_authority: Pubkey) -> Result<()> {
+        require!(new_authority != Pubkey::default(), InsuranceError::InvalidRoleHolder);
+        ctx.accounts.insurance_pool.pending_authority = new_authority;
+        Ok(())
+    }
+
+    // The nominated key signs to take over as admin
+    pub fn accept_authority_transfer(ctx: Context<AcceptAuthorityTransfer>) -> Result<()> {
+        let pool = &mut ctx.accounts.insurance_pool;
+        pool.authority = pool.pending_authority;
+        pool.pending_authority = Pubkey::default();
+        Ok(())
+    }
+
+    // Admin withdraws a nomination that has not been accepted yet
+    pub fn cancel_authority_transfer(ctx: Context<ManageRole>) -> Result<()> {
+        let pool = &mut ctx.accounts.insurance_pool;
+        require!(pool.pending_authority != Pubkey::default(), InsuranceError::NoPendingAuthority);
+        pool.pending_authority = Pubkey::default();
+        Ok(())
+    }
+
+    // Pauser replaces the pool's pause flags; a zero mask resumes everything
+    pub fn set_pause_flags(ctx: Context<SetPauseFlags>, flags: u8) -> Result<()> {
+        require!(flags & !PAUSE_ALL == 0, InsuranceError::InvalidPauseFlags);
+        ctx.accounts.insurance_pool.pause_flags = flags;
+        Ok(())
+    }
+
+    // Admin designates the multisig that must approve claims paying out more than the threshold;
+    // a zero threshold lets the claims assessor approve any amount
+    pub fn set_large_claim_approval(
+        ctx: Context<ManageRole>,
+        multisig_signer: Pubkey,
+        large_claim_threshold: u64,
+    ) -> Result<()> {
+        require!(
+            large_claim_threshold == 0 || multisig_signer != Pubkey::default(),
+            InsuranceError::InvalidRoleHolder
+        );
+        let pool = &mut ctx.accounts.insurance_pool;
+        pool.multisig_signer = multisig_signer;
+        pool.large_claim_threshold = large_claim_threshold;
+        Ok(())
+    }
+
+    // Create an M-of-N multisig; its signer PDA can then hold pool roles
+    pub fn create_multisig(ctx: Context<CreateMultisig>, owners: Vec<Pubkey>, threshold: u64) -> Result<()> {
+        validate_owners(&owners, threshold)?;
+
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.owners = owners;
+        multisig.threshold = threshold;
+        multisig.signer_bump = ctx.bumps.multisig_signer;
+        multisig.owner_set_seqno = 0;
+        multisig.transaction_count = 0;
+        Ok(())
+    }
+
+    // An owner proposes an instruction for the multisig signer to execute; proposing counts as approval
+    pub fn create_multisig_transaction(
+        ctx: Context<CreateMultisigTransaction>,
+        program_id: Pubkey,
+        accounts: Vec<TransactionAccount>,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        let owner_index = multisig.owner_index(ctx.accounts.proposer.key)?;
+
+        let mut signers = vec![false; multisig.owners.len()];
+        signers[owner_index] = true;
+
+        let transaction = &mut ctx.accounts.transaction;
+        transaction.multisig = multisig.key();
+        transaction.index = multisig.transaction_count;
+        transaction.proposer = ctx.accounts.proposer.key();
+        transaction.program_id = program_id;
+        transaction.accounts = accounts;
+        transaction.data = data;
+        transaction.signers = signers;
+        transaction.owner_set_seqno = multisig.owner_set_seqno;
+        transaction.created_at = Clock::get()?.unix_timestamp;
+        transaction.did_execute = false;
+        transaction.executor = Pubkey::default();
+        transaction.executed_at = 0;
+
+        multisig.transaction_count += 1;
+        Ok(())
+    }
+
+    // Another owner adds their approval to a pending transaction
+    pub fn approve_multisig_transaction(ctx: Context<ApproveMultisigTransaction>) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        let transaction = &mut ctx.accounts.transaction;
+        require!(!transaction.did_execute, InsuranceError::AlreadyExecuted);
+        require!(
+            transaction.owner_set_seqno == multisig.owner_set_seqno,
+            InsuranceError::OwnerSetChanged
+        );
+
+        let owner_index = multisig.owner_index(ctx.accounts.owner.key)?;
+        transaction.signers[owner_index] = true;
+        Ok(())
+    }
+
+    // Once enough owners have approved, anyone may execute the transaction. The target
+    // accounts are passed as remaining accounts and the multisig signer PDA signs the call.
+    pub fn execute_multisig_transaction(ctx: Context<ExecuteMultisigTransaction>) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        let transaction = &mut ctx.accounts.transaction;
+        require!(!transaction.did_execute, InsuranceError::AlreadyExecuted);
+        require!(
+            transaction.owner_set_seqno == multisig.owner_set_seqno,
+            InsuranceError::OwnerSetChanged
+        );
+        let approvals = transaction.signers.iter().filter(|approved| **approved).count() as u64;
+        require!(approvals >= multisig.threshold, InsuranceError::NotEnoughApprovals);
+
+        // Record the execution before the call so the transaction cannot be replayed through re-entry
+        transaction.did_execute = true;
+        transaction.executor = ctx.accounts.executor.key();
+        transaction.executed_at = Clock::get()?.unix_timestamp;
+        transaction.exit(&crate::ID)?;
+
+        let signer_key = ctx.accounts.multisig_signer.key();
+        let mut ix: Instruction = transaction.instruction();
+        for meta in ix.accounts.iter_mut() {
+            if meta.pubkey == signer_key {
+                meta.is_signer = true;
+            }
+        }
+
+        let multisig_key = multisig.key();
+        let seeds = &[b"multisig_signer".as_ref(), multisig_key.as_ref(), &[multisig.signer_bump]];
+        invoke_signed(&ix, ctx.remaining_accounts, &[&seeds[..]])?;
+        Ok(())
+    }
+
+    // Replace the owner set and threshold; only callable by the multisig itself.
+    // Transactions proposed under the old owner set can no longer be approved or executed.
+    pub fn change_multisig_owners(ctx: Context<ChangeMultisigOwners>, owners: Vec<Pubkey>, threshold: u64) -> Result<()> {
+        validate_owners(&owners, threshold)?;
+
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.owners = owners;
+        multisig.threshold = threshold;
+        multisig.owner_set_seqno += 1;
+        Ok(())
+    }
+
+    // Create the per-user profile that indexes a user's policies in a pool
+    pub fn initialize_user_profile(ctx: Context<InitializeUserProfile>) -> Result<()> {
+        let profile = &mut ctx.accounts.user_profile;
+        profile.pool = ctx.accounts.insurance_pool.key();
+        profile.user = ctx.accounts.user.key();
+        profile.policy_count = 0;
+        profile.bump = ctx.bumps.user_profile;
+        Ok(())
+    }
+
+    // Create the account that counts a user's policies in force under one product
+    pub fn initialize_product_holding(ctx: Context<InitializeProductHolding>) -> Result<()> {
+        let holding = &mut ctx.accounts.product_holding;
+        holding.product = ctx.accounts.product.key();
+        holding.user = ctx.accounts.user.key();
+        holding.active_policies = 0;
+        holding.bump = ctx.bumps.product_holding;
+        Ok(())
+    }
+
+    // Admin adds a product to the pool's catalog
+    pub fn create_product(ctx: Context<CreateProduct>, config: ProductConfig) -> Result<()> {
+        add_product(&mut ctx.accounts.insurance_pool, &mut ctx.accounts.product, config)
+    }
+
+    // Admin updates a product's terms; existing policies keep the terms they were sold with
+    pub fn update_product(ctx: Context<UpdateProduct>, config: ProductConfig) -> Result<()> {
+        validate_product_config(&ctx.accounts.insurance_pool, &config)?;
+        require!(
+            config.capacity >= ctx.accounts.product.active_coverage,
+            InsuranceError::InvalidProductConfig
+        );
+        ctx.accounts.product.apply_config(config);
         Ok(())
     }
 
-    // Purchase insurance
+    // Purchase insurance under one of the pool's products. The quoted premium is paid into the
+    // pool vault in the same instruction, either in full or as the first of several installments
+    #[allow(clippy::too_many_arguments)]
     pub fn purchase_insurance(
         ctx: Context<PurchaseInsurance>,
-        deposit_amount: u64,
-        premium_amount: u64,
-        coverage_amount: u64
+        max_premium: u64,
+        coverage_amount: u64,
+        deductible: u64,
+        per_claim_limit: u64,
+        term: i64,
+        start_time: Option<i64>,
+        installments: u8,
     ) -> Result<()> {
+        ctx.accounts.insurance_pool.require_not_paused(PAUSE_NEW_POLICIES)?;
+
+        let product = &ctx.accounts.product;
+        require!(product.enabled, InsuranceError::ProductDisabled);
+        require!(
+            ctx.accounts.product_holding.active_policies < product.max_active_policies,
+            InsuranceError::TooManyActivePolicies
+        );
+        require!(product.term_options.contains(&term), InsuranceError::InvalidDuration);
+        require!(
+            coverage_amount > 0 && coverage_amount <= product.max_coverage_per_policy,
+            InsuranceError::InvalidPolicyTerms
+        );
+        require!(
+            product.active_coverage + coverage_amount <= product.capacity,
+            InsuranceError::ProductCapacityExceeded
+        );
+        require!(deductible >= product.min_deductible, InsuranceError::InvalidPolicyTerms);
+        // Every installment has to buy at least a second of cover
+        require!(
+            installments >= 1 && installments <= product.max_installments && term >= installments as i64,
+            InsuranceError::InvalidPolicyTerms
+        );
+
+        let premium_amount = quote_premium(
+            product,
+            &ctx.accounts.insurance_pool,
+            ctx.accounts.vault.amount,
+            coverage_amount,
+            coverage_amount,
+            term,
+        )?;
+        require!(premium_amount <= max_premium, InsuranceError::PremiumSlippageExceeded);
+
+        // Cover starts now unless scheduled for a later time
+        let current_time = Clock::get()?.unix_timestamp;
+        let start_time = match start_time {
+            Some(start_time) => {
+                require!(
+                    start_time >= current_time && start_time - current_time <= MAX_START_DELAY,
+                    InsuranceError::InvalidStartTime
+                );
+                start_time
+            }
+            None => current_time,
+        };
+
+        require!(
+            per_claim_limit > 0 && per_claim_limit <= coverage_amount,
+            InsuranceError::InvalidPolicyTerms
+        );
+
+        let profile = &mut ctx.accounts.user_profile;
         let policy = &mut ctx.accounts.insurance_policy;
         policy.user = *ctx.accounts.user.key;
-        policy.deposit_amount = deposit_amount;
+        policy.pool = ctx.accounts.insurance_pool.key();
+        policy.index = profile.policy_count;
+        policy.product = ctx.accounts.product.key();
         policy.premium_amount = premium_amount;
         policy.coverage_amount = coverage_amount;
-        policy.start_time = Clock::get()?.unix_timestamp;
-        policy.end_time = Clock::get()?.unix_timestamp + 30 * 24 * 60 * 60; // 30-day coverage
+        policy.deductible = deductible;
+        policy.coinsurance_bps = ctx.accounts.product.coinsurance_bps;
+        policy.per_claim_limit = per_claim_limit;
+        policy.start_time = start_time;
+        policy.end_time = start_time.checked_add(term).ok_or(InsuranceError::MathOverflow)?;
+        policy.term = term;
+        policy.auto_renew = false;
+        policy.auto_renew_max_premium = 0;
+        policy.renewal_count = 0;
+        policy.renewed_term_start = 0;
         policy.is_active = true;
+        policy.claim_count = 0;
+        policy.claimed_to_date = 0;
+        policy.open_claims = 0;
+        policy.installment_count = installments;
+        policy.installments_paid = 0;
+        policy.installment_period = term / installments as i64;
+        policy.paid_through = start_time;
+        policy.premium_paid = 0;
+        policy.lapsed = false;
+
+        // Collect the full premium, or the first installment
+        let first_payment = policy.next_installment_amount();
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, first_payment)?;
+        policy.record_installment(first_payment);
+
+        profile.policy_count += 1;
 
         let insurance_pool = &mut ctx.accounts.insurance_pool;
-        insurance_pool.total_premium_collected += premium_amount;
+        insurance_pool.total_premium_collected += first_payment;
+        let product = &mut ctx.accounts.product;
+        reserve_coverage(insurance_pool, product, coverage_amount);
+        ctx.accounts.product_holding.active_policies += 1;
 
         Ok(())
     }
 
     // Cancel insurance and refund pro-rated premium
     pub fn cancel_policy(ctx: Context<CancelPolicy>) -> Result<()> {
+        // Refunds leave the vault, so they stop with claim payouts
+        ctx.accounts.insurance_pool.require_not_paused(PAUSE_CLAIM_PAYOUTS)?;
+
         let policy = &mut ctx.accounts.insurance_policy;
         require!(policy.is_active, InsuranceError::PolicyNotActive);
+        require!(policy.open_claims == 0, InsuranceError::OpenClaimsPending);
 
         let current_time = Clock::get()?.unix_timestamp;
         require!(current_time < policy.end_time, InsuranceError::PolicyExpired);
 
-        let remaining_time = policy.end_time - current_time;
-        let total_duration = policy.end_time - policy.start_time;
+        // Only premium paid for cover not yet used is refunded; cover that has not started
+        // yet is refunded in full
+        let remaining_time = (policy.paid_through - current_time.max(policy.start_time)).max(0);
+        let total_duration = policy.paid_through - policy.start_time;
 
         // Pro-rated refund calculation
-        let refund_amount = (policy.premium_amount as u128 * remaining_time as u128 / total_duration as u128) as u64;
+        let refund_amount = if total_duration > 0 {
+            (policy.premium_paid as u128 * remaining_time as u128 / total_duration as u128) as u64
+        } else {
+            0
+        };
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.user_token_account,
+            &ctx.accounts.pool_authority,
+            ctx.accounts.insurance_pool.pool_authority_bump,
+            refund_amount,
+        )?;
+
+        close_policy(policy, &mut ctx.accounts.product_holding); // Mark the policy as canceled
+
+        release_coverage(
+            &mut ctx.accounts.insurance_pool,
+            &mut ctx.accounts.product,
+            policy.reserved_coverage(),
+        );
+
+        Ok(())
+    }
+
+    // Policyholder files a claim against an active policy
+    pub fn submit_claim(
+        ctx: Context<SubmitClaim>,
+        requested_amount: u64,
+        evidence_hash: [u8; 32],
+        evidence_uri: String,
+    ) -> Result<()> {
+        ctx.accounts.insurance_pool.require_not_paused(PAUSE_CLAIM_SUBMISSION)?;
+
+        require!(requested_amount > 0, InsuranceError::InvalidAmount);
+        require!(evidence_uri.len() <= Claim::MAX_URI_LEN, InsuranceError::EvidenceUriTooLong);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let policy = &mut ctx.accounts.insurance_policy;
+        start_renewed_term(policy, current_time);
+
+        require!(policy.is_active, InsuranceError::PolicyNotActive);
+        require!(requested_amount <= policy.remaining_coverage(), InsuranceError::ClaimExceedsCoverage);
+        require!(current_time >= policy.start_time, InsuranceError::PolicyNotStarted);
+        require!(current_time <= policy.end_time, InsuranceError::PolicyExpired);
+        require!(current_time <= policy.paid_through, InsuranceError::PremiumNotPaid);
 
-        **ctx.accounts.user.to_account_info().try_borrow_mut_lamports()? += refund_amount;
-        **ctx.accounts.insurance_pool.to_account_info().try_borrow_mut_lamports()? -= refund_amount;
+        let claim = &mut ctx.accounts.claim;
+        claim.policy = policy.key();
+        claim.claimant = ctx.accounts.user.key();
+        claim.index = policy.claim_count;
+        claim.requested_amount = requested_amount;
+        claim.assessed_loss = 0;
+        claim.approved_amount = 0;
+        claim.evidence_hash = evidence_hash;
+        claim.evidence_uri = evidence_uri;
+        claim.submitted_at = current_time;
+        claim.resolved_at = 0;
+        claim.status = ClaimStatus::Submitted;
 
-        policy.is_active = false; // Mark the policy as canceled
+        policy.claim_count += 1;
+        policy.open_claims += 1;
 
         Ok(())
     }
 
-    // Submit and approve claim by admin
-    pub fn approve_claim(ctx: Context<ApproveClaim>) -> Result<()> {
+    // Admin picks up a submitted claim for assessment
+    pub fn review_claim(ctx: Context<ReviewClaim>) -> Result<()> {
+        let claim = &mut ctx.accounts.claim;
+        require!(claim.status == ClaimStatus::Submitted, InsuranceError::InvalidClaimStatus);
+        claim.status = ClaimStatus::UnderReview;
+        Ok(())
+    }
+
+    // Approve a submitted claim for the assessed loss and pay the net amount due under the
+    // policy terms out of the pool vault
+    pub fn approve_claim(ctx: Context<ApproveClaim>, assessed_loss: u64) -> Result<()> {
+        ctx.accounts.insurance_pool.require_not_paused(PAUSE_CLAIM_PAYOUTS)?;
+
+        let claim = &mut ctx.accounts.claim;
+        require!(
+            claim.status == ClaimStatus::Submitted || claim.status == ClaimStatus::UnderReview,
+            InsuranceError::InvalidClaimStatus
+        );
+
         let policy = &mut ctx.accounts.insurance_policy;
         require!(policy.is_active, InsuranceError::PolicyNotActive);
 
+        require!(assessed_loss > 0, InsuranceError::InvalidAmount);
+        require!(assessed_loss <= claim.requested_amount, InsuranceError::ClaimExceedsRequest);
+        require!(policy.remaining_coverage() > 0, InsuranceError::CoverageExhausted);
+
+        // The payout can never exceed the policy's remaining coverage
+        let payout = policy.net_payable(assessed_loss);
+        require!(payout > 0, InsuranceError::BelowDeductible);
+        require!(ctx.accounts.vault.amount >= payout, InsuranceError::NotEnoughFunds);
+
+        // Large payouts need the multisig rather than the claims assessor; the policy's earlier
+        // payouts count too, so a loss cannot be split into claims below the threshold
+        let pool = &ctx.accounts.insurance_pool;
+        if pool.large_claim_threshold > 0 && policy.claimed_to_date + payout > pool.large_claim_threshold {
+            require_keys_eq!(
+                ctx.accounts.claims_assessor.key(),
+                pool.multisig_signer,
+                InsuranceError::MultisigApprovalRequired
+            );
+        }
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.policyholder_token_account,
+            &ctx.accounts.pool_authority,
+            ctx.accounts.insurance_pool.pool_authority_bump,
+            payout,
+        )?;
+
+        // Accounting only moves once the transfer has gone through
         let insurance_pool = &mut ctx.accounts.insurance_pool;
+        insurance_pool.total_claims_paid += payout;
+        // A pending renewed term reinstates the paid-out coverage, so it stays reserved
+        if policy.renewed_term_start == 0 {
+            release_coverage(insurance_pool, &mut ctx.accounts.product, payout);
+        }
+
+        policy.claimed_to_date += payout;
+        policy.open_claims -= 1;
+        // A renewed term that has not started yet brings the coverage back, so the policy stays in force
+        if policy.remaining_coverage() == 0 && policy.renewed_term_start == 0 {
+            close_policy(policy, &mut ctx.accounts.product_holding); // Coverage is exhausted
+        }
+
+        claim.assessed_loss = assessed_loss;
+        claim.approved_amount = payout;
+        claim.resolved_at = Clock::get()?.unix_timestamp;
+        claim.status = ClaimStatus::Paid;
+
+        Ok(())
+    }
+
+    // Reject a submitted claim by admin
+    pub fn reject_claim(ctx: Context<RejectClaim>) -> Result<()> {
+        let claim = &mut ctx.accounts.claim;
         require!(
-            insurance_pool.total_claims_paid + policy.coverage_amount
-                <= insurance_pool.total_premium_collected,
-            InsuranceError::NotEnoughFunds
+            claim.status == ClaimStatus::Submitted || claim.status == ClaimStatus::UnderReview,
+            InsuranceError::InvalidClaimStatus
         );
 
-        insurance_pool.total_claims_paid += policy.coverage_amount;
-        policy.is_active = false; // Mark the policy as inactive after approval
+        claim.resolved_at = Clock::get()?.unix_timestamp;
+        claim.status = ClaimStatus::Rejected;
+
+        ctx.accounts.insurance_policy.open_claims -= 1;
 
         Ok(())
     }
 
-    // Admin withdraws premium funds from the insurance pool
-    pub fn withdraw_premium(ctx: Context<WithdrawPremium>, amount: u64) -> Result<()> {
-        let insurance_pool = &mut ctx.accounts.insurance_pool;
+    // Treasurer queues a withdrawal of premium funds; it unlocks no sooner than the pool's withdrawal delay
+    pub fn queue_withdrawal(ctx: Context<QueueWithdrawal>, amount: u64, unlock_time: Option<i64>) -> Result<()> {
+        ctx.accounts.insurance_pool.require_not_paused(PAUSE_TREASURY)?;
+        require!(amount > 0, InsuranceError::InvalidAmount);
+
+        let pool = &mut ctx.accounts.insurance_pool;
+        let current_time = Clock::get()?.unix_timestamp;
+        let earliest_unlock = current_time
+            .checked_add(pool.withdrawal_delay)
+            .ok_or(InsuranceError::MathOverflow)?;
+        let unlock_time = match unlock_time {
+            Some(unlock_time) => {
+                require!(unlock_time >= earliest_unlock, InsuranceError::InvalidUnlockTime);
+                unlock_time
+            }
+            None => earliest_unlock,
+        };
+
+        let request = &mut ctx.accounts.withdrawal_request;
+        request.pool = pool.key();
+        request.id = pool.next_withdrawal_id;
+        request.amount = amount;
+        request.destination = ctx.accounts.destination.key();
+        request.queued_by = ctx.accounts.treasurer.key();
+        request.queued_at = current_time;
+        request.unlock_time = unlock_time;
+
+        pool.next_withdrawal_id += 1;
+        Ok(())
+    }
+
+    // Pauser vetoes a queued withdrawal during its delay
+    pub fn cancel_withdrawal(_ctx: Context<CancelWithdrawal>) -> Result<()> {
+        Ok(())
+    }
+
+    // Pay out a queued withdrawal once unlocked. Anyone may execute it since the destination is fixed;
+    // only free capital can leave, so coverage reserves stay in the vault.
+    pub fn withdraw_premium(ctx: Context<WithdrawPremium>) -> Result<()> {
+        ctx.accounts.insurance_pool.require_not_paused(PAUSE_TREASURY)?;
+
+        let request = &ctx.accounts.withdrawal_request;
+        require!(
+            Clock::get()?.unix_timestamp >= request.unlock_time,
+            InsuranceError::WithdrawalLocked
+        );
         require!(
-            insurance_pool.total_premium_collected - insurance_pool.total_claims_paid >= amount,
-            InsuranceError::NotEnoughFunds
+            request.amount <= free_capital(&ctx.accounts.insurance_pool, ctx.accounts.vault.amount),
+            InsuranceError::InsufficientFreeCapital
         );
-        
-        // Transfer lamports to admin (the insurance protocol administrator)
-        **ctx.accounts.admin.to_account_info().try_borrow_mut_lamports()? += amount;
-        **ctx.accounts.insurance_pool.to_account_info().try_borrow_mut_lamports()? -= amount;
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.destination,
+            &ctx.accounts.pool_authority,
+            ctx.accounts.insurance_pool.pool_authority_bump,
+            request.amount,
+        )?;
 
         Ok(())
     }
@@ -103,119 +643,1284 @@ pub mod insurance_protocol {
         Ok(())
     }
 
-    // Auto-expire policy if it has passed the expiration time
+    // Auto-expire policy if it has passed the expiration time and has no claims left to settle.
+    // Expiry only frees reserved capital, so it keeps running while the pool is paused.
     pub fn process_policy_expiration(ctx: Context<ProcessExpiration>) -> Result<()> {
         let policy = &mut ctx.accounts.insurance_policy;
         let current_time = Clock::get()?.unix_timestamp;
 
-        if current_time > policy.end_time && policy.is_active {
-            policy.is_active = false;
-            return err!(InsuranceError::PolicyExpired);
-        }
+        require!(policy.is_active, InsuranceError::PolicyNotActive);
+        require!(current_time > policy.end_time, InsuranceError::PolicyNotExpired);
+        require!(policy.open_claims == 0, InsuranceError::OpenClaimsPending);
+        close_policy(policy, &mut ctx.accounts.product_holding);
+
+        // Expired cover no longer needs capital reserved against it
+        release_coverage(
+            &mut ctx.accounts.insurance_pool,
+            &mut ctx.accounts.product,
+            policy.reserved_coverage(),
+        );
 
         Ok(())
     }
 
-    // Adjust coverage dynamically
-    pub fn adjust_coverage(ctx: Context<AdjustCoverage>, new_coverage_amount: u64) -> Result<()> {
+    // Permissionless crank: lapse a policy whose next installment is overdue past the grace period
+    pub fn lapse_policy(ctx: Context<LapsePolicy>) -> Result<()> {
+        // Holders cannot be lapsed while installment payments are halted
+        ctx.accounts.insurance_pool.require_not_paused(PAUSE_NEW_POLICIES)?;
+
         let policy = &mut ctx.accounts.insurance_policy;
         require!(policy.is_active, InsuranceError::PolicyNotActive);
-        policy.coverage_amount = new_coverage_amount;
+        require!(policy.paid_through < policy.end_time, InsuranceError::PolicyNotOverdue);
+        require!(policy.open_claims == 0, InsuranceError::OpenClaimsPending);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            current_time > policy.paid_through + ctx.accounts.insurance_pool.grace_period,
+            InsuranceError::PolicyNotOverdue
+        );
+
+        close_policy(policy, &mut ctx.accounts.product_holding);
+        policy.lapsed = true;
+
+        release_coverage(
+            &mut ctx.accounts.insurance_pool,
+            &mut ctx.accounts.product,
+            policy.reserved_coverage(),
+        );
+
         Ok(())
     }
 
-    // Pay premium with token (SPL token support)
-    pub fn pay_premium_with_token(ctx: Context<PayPremiumWithToken>, amount: u64) -> Result<()> {
-        let pool = &mut ctx.accounts.insurance_pool;  // Fix: change to insurance_pool
-        require!(pool.total_premium_collected > 0, InsuranceError::NotEnoughFunds);  // Additional check for premium pool
+    // Policyholder renews for another term at the current price, keeping the same policy account
+    pub fn renew_policy(ctx: Context<RenewPolicy>, max_premium: u64) -> Result<()> {
+        ctx.accounts.insurance_pool.require_not_paused(PAUSE_NEW_POLICIES)?;
 
-        let token_account = &mut ctx.accounts.user_token_account;
-        let pool_token_account = &mut ctx.accounts.pool_token_account;
+        let current_time = Clock::get()?.unix_timestamp;
+        start_renewed_term(&mut ctx.accounts.insurance_policy, current_time);
+
+        let premium_amount = quote_renewal(
+            &ctx.accounts.insurance_policy,
+            &ctx.accounts.product,
+            &ctx.accounts.insurance_pool,
+            ctx.accounts.vault.amount,
+            current_time,
+        )?;
+        require!(premium_amount <= max_premium, InsuranceError::PremiumSlippageExceeded);
 
         let cpi_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
             token::Transfer {
-                from: token_account.to_account_info(),
-                to: pool_token_account.to_account_info(),
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
                 authority: ctx.accounts.user.to_account_info(),
             },
         );
-        token::transfer(cpi_ctx, amount)?;  // Correct use of token::transfer
+        token::transfer(cpi_ctx, premium_amount)?;
 
-        pool.total_premium_collected += amount;
+        apply_renewal(
+            &mut ctx.accounts.insurance_policy,
+            &mut ctx.accounts.product,
+            &mut ctx.accounts.insurance_pool,
+            premium_amount,
+        )
+    }
 
+    // Opt in or out of automatic renewal. The policyholder must also approve the pool_authority
+    // PDA as a delegate on their token account for the renewal premium.
+    pub fn set_auto_renew(ctx: Context<SetAutoRenew>, enabled: bool, max_premium: u64) -> Result<()> {
+        let policy = &mut ctx.accounts.insurance_policy;
+        policy.auto_renew = enabled;
+        policy.auto_renew_max_premium = max_premium;
         Ok(())
     }
 
-    // Stake into the insurance pool for liquidity
-    pub fn stake_into_pool(ctx: Context<StakeIntoPool>, amount: u64) -> Result<()> {
-        let pool = &mut ctx.accounts.insurance_pool;  // Fix: use insurance_pool here
+    // Permissionless crank: renew an auto-renewing policy inside its renewal window, pulling the
+    // premium from the policyholder's token account through the pool_authority delegate
+    pub fn process_auto_renewal(ctx: Context<ProcessAutoRenewal>) -> Result<()> {
+        ctx.accounts.insurance_pool.require_not_paused(PAUSE_NEW_POLICIES)?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        start_renewed_term(&mut ctx.accounts.insurance_policy, current_time);
+
+        let policy = &ctx.accounts.insurance_policy;
+        require!(policy.auto_renew, InsuranceError::AutoRenewDisabled);
+
+        let premium_amount = quote_renewal(
+            policy,
+            &ctx.accounts.product,
+            &ctx.accounts.insurance_pool,
+            ctx.accounts.vault.amount,
+            current_time,
+        )?;
+        require!(
+            premium_amount <= policy.auto_renew_max_premium,
+            InsuranceError::PremiumSlippageExceeded
+        );
+
+        let seeds: &[&[u8]] = &[b"insurance_pool".as_ref(), &[ctx.accounts.insurance_pool.pool_authority_bump]];
+        let signer_seeds = &[seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx, premium_amount)?;
+
+        apply_renewal(
+            &mut ctx.accounts.insurance_policy,
+            &mut ctx.accounts.product,
+            &mut ctx.accounts.insurance_pool,
+            premium_amount,
+        )
+    }
+
+    // Reduce the coverage on an active policy
+    pub fn adjust_coverage(ctx: Context<AdjustCoverage>, new_coverage_amount: u64) -> Result<()> {
+        ctx.accounts.insurance_pool.require_not_paused(PAUSE_NEW_POLICIES)?;
+
+        let policy = &mut ctx.accounts.insurance_policy;
+        let insurance_pool = &mut ctx.accounts.insurance_pool;
+        let product = &mut ctx.accounts.product;
+        start_renewed_term(policy, Clock::get()?.unix_timestamp);
+
+        require!(policy.is_active, InsuranceError::PolicyNotActive);
+        require!(new_coverage_amount > policy.claimed_to_date, InsuranceError::ClaimExceedsCoverage);
+        // More cover has to be bought as a new policy so it is priced and capital-checked
+        require!(
+            new_coverage_amount <= policy.coverage_amount,
+            InsuranceError::CoverageIncreaseNotAllowed
+        );
+
+        release_coverage(insurance_pool, product, policy.reserved_coverage());
+        policy.coverage_amount = new_coverage_amount;
+        reserve_coverage(insurance_pool, product, policy.reserved_coverage());
+        Ok(())
+    }
+
+    // Pay the next premium installment on a policy with token (SPL token support)
+    pub fn pay_premium_with_token(ctx: Context<PayPremiumWithToken>) -> Result<()> {
+        ctx.accounts.insurance_pool.require_not_paused(PAUSE_NEW_POLICIES)?;
 
-        let token_account = &mut ctx.accounts.user_token_account;
-        let pool_token_account = &mut ctx.accounts.pool_token_account;
+        let policy = &mut ctx.accounts.insurance_policy;
+        require!(policy.is_active, InsuranceError::PolicyNotActive);
+        require!(
+            policy.installments_paid < policy.installment_count,
+            InsuranceError::PremiumFullyPaid
+        );
+
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            current_time <= policy.paid_through + ctx.accounts.insurance_pool.grace_period,
+            InsuranceError::PolicyLapsed
+        );
 
+        let amount = policy.next_installment_amount();
         let cpi_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
             token::Transfer {
-                from: token_account.to_account_info(),
-                to: pool_token_account.to_account_info(),
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
                 authority: ctx.accounts.user.to_account_info(),
             },
         );
         token::transfer(cpi_ctx, amount)?;
 
-        pool.total_premium_collected += amount;  // Record the stake in the pool
+        policy.record_installment(amount);
+        ctx.accounts.insurance_pool.total_premium_collected += amount;
 
         Ok(())
     }
 
-    // Governance voting on protocol changes
-    pub fn submit_governance_vote(ctx: Context<SubmitVote>, proposal_id: u64, vote: bool) -> Result<()> {
-        let governance = &mut ctx.accounts.governance;
-        let vote_record = &mut ctx.accounts.vote_record;
+    // Stake into the insurance pool for liquidity, minting LP shares pro-rata to pool NAV
+    pub fn stake_into_pool(ctx: Context<StakeIntoPool>, amount: u64) -> Result<()> {
+        ctx.accounts.insurance_pool.require_not_paused(PAUSE_STAKING)?;
 
-        vote_record.user = *ctx.accounts.user.key;
-        vote_record.proposal_id = proposal_id;
-        vote_record.vote = vote;
-        vote_record.timestamp = Clock::get()?.unix_timestamp;
+        require!(amount > 0, InsuranceError::InvalidAmount);
 
-        if vote {
-            governance.yes_votes += 1;
+        // NAV is the vault balance before this deposit lands
+        let nav = ctx.accounts.vault.amount;
+        let share_supply = ctx.accounts.lp_mint.supply;
+        let mut shares = shares_for_deposit(amount, nav, share_supply)?;
+
+        // The first stake locks shares for premiums already in the vault plus MINIMUM_LIQUIDITY of its own
+        let locked_shares = if share_supply == 0 {
+            shares = shares.checked_sub(MINIMUM_LIQUIDITY).ok_or(InsuranceError::InvalidAmount)?;
+            nav.checked_add(MINIMUM_LIQUIDITY).ok_or(InsuranceError::MathOverflow)?
         } else {
-            governance.no_votes += 1;
+            0
+        };
+        require!(shares > 0, InsuranceError::InvalidAmount);
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, amount)?;
+
+        let seeds: &[&[u8]] = &[b"insurance_pool".as_ref(), &[ctx.accounts.insurance_pool.pool_authority_bump]];
+        let signer_seeds = &[seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::MintTo {
+                mint: ctx.accounts.lp_mint.to_account_info(),
+                to: ctx.accounts.user_share_account.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::mint_to(cpi_ctx, shares)?;
+
+        if locked_shares > 0 {
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::MintTo {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    to: ctx.accounts.share_escrow.to_account_info(),
+                    authority: ctx.accounts.pool_authority.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token::mint_to(cpi_ctx, locked_shares)?;
         }
 
         Ok(())
     }
-}
 
-// Define the structure for an insurance policy
-#[account]
-pub struct InsurancePolicy {
-    pub user: Pubkey,
-    pub deposit_amount: u64,
-    pub coverage_amount: u64,
-    pub premium_amount: u64,
-    pub start_time: i64,
-    pub end_time: i64,
-    pub is_active: bool,
-}
+    // Queue LP shares for withdrawal; they stay in the share supply and keep absorbing losses
+    pub fn request_unstake(ctx: Context<RequestUnstake>, shares: u64) -> Result<()> {
+        ctx.accounts.insurance_pool.require_not_paused(PAUSE_UNSTAKING)?;
 
-// Define the structure for the insurance pool that holds total premiums collected and claims paid
-#[account]
-pub struct InsurancePool {
-    pub total_premium_collected: u64,
-    pub total_claims_paid: u64,
-    pub authority: Pubkey, // Using a PDA to manage the pool
-}
+        require!(shares > 0, InsuranceError::InvalidAmount);
 
-impl InsurancePolicy {
-    const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 1;
-}
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.user_share_account.to_account_info(),
+                to: ctx.accounts.share_escrow.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, shares)?;
 
-impl InsurancePool {
-    const LEN: usize = 8 + 8 + 32;
-}
+        let pool = &mut ctx.accounts.insurance_pool;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        let request = &mut ctx.accounts.unstake_request;
+        request.pool = pool.key();
+        request.owner = ctx.accounts.user.key();
+        request.ticket = pool.next_unstake_ticket;
+        request.shares = shares;
+        request.requested_at = current_time;
+        request.unlock_time = current_time + pool.unstake_cooldown;
+
+        pool.next_unstake_ticket += 1;
+        pool.pending_unstake_shares += shares;
+
+        Ok(())
+    }
+
+    // Pay out the request at the head of the withdrawal queue once its cooldown has elapsed.
+    // Shares are priced at completion time, and only free capital is paid out; if that is short
+    // the head request is filled partially and later requests wait behind it.
+    pub fn complete_unstake(ctx: Context<CompleteUnstake>) -> Result<()> {
+        ctx.accounts.insurance_pool.require_not_paused(PAUSE_UNSTAKING)?;
+
+        let pool = &ctx.accounts.insurance_pool;
+        let request = &ctx.accounts.unstake_request;
+        require!(request.ticket == pool.unstake_queue_head, InsuranceError::NotQueueHead);
+        require!(
+            Clock::get()?.unix_timestamp >= request.unlock_time,
+            InsuranceError::CooldownNotElapsed
+        );
+
+        let nav = ctx.accounts.vault.amount;
+        let supply = ctx.accounts.lp_mint.supply;
+        let free = free_capital(pool, nav);
+
+        let full_amount = assets_for_shares(request.shares, nav, supply)?;
+        let (shares, amount) = if full_amount <= free {
+            (request.shares, full_amount)
+        } else {
+            let filled = (request.shares as u128 * free as u128 / full_amount as u128) as u64;
+            (filled, assets_for_shares(filled, nav, supply)?)
+        };
+        require!(shares > 0, InsuranceError::InsufficientFreeCapital);
+
+        let seeds: &[&[u8]] = &[b"insurance_pool".as_ref(), &[pool.pool_authority_bump]];
+        let signer_seeds = &[seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Burn {
+                mint: ctx.accounts.lp_mint.to_account_info(),
+                from: ctx.accounts.share_escrow.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::burn(cpi_ctx, shares)?;
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.owner_token_account,
+            &ctx.accounts.pool_authority,
+            pool.pool_authority_bump,
+            amount,
+        )?;
+
+        let pool = &mut ctx.accounts.insurance_pool;
+        pool.pending_unstake_shares -= shares;
+
+        let request = &mut ctx.accounts.unstake_request;
+        request.shares -= shares;
+        if request.shares == 0 {
+            pool.unstake_queue_head += 1;
+            request.close(ctx.accounts.owner.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    // Admin sets up the pool's governance account, which numbers its proposals
+    pub fn initialize_governance(ctx: Context<InitializeGovernance>, config: GovernanceConfig) -> Result<()> {
+        validate_governance_config(&config)?;
+
+        let governance = &mut ctx.accounts.governance;
+        governance.apply_config(&config);
+        governance.pool = ctx.accounts.insurance_pool.key();
+        governance.total_proposals = 0;
+        governance.bump = ctx.bumps.governance;
+        governance.escrow = ctx.accounts.governance_escrow.key();
+        governance.total_deposited = 0;
+        governance.recent_deposits = 0;
+        governance.recent_deposit_at = 0;
+        Ok(())
+    }
+
+    // Create the account that tracks a voter's escrowed LP shares
+    pub fn create_voter_deposit(ctx: Context<CreateVoterDeposit>) -> Result<()> {
+        let deposit = &mut ctx.accounts.voter_deposit;
+        deposit.governance = ctx.accounts.governance.key();
+        deposit.owner = ctx.accounts.owner.key();
+        deposit.shares = 0;
+        deposit.pending = Vec::new();
+        deposit.locked_until = 0;
+        deposit.bump = ctx.bumps.voter_deposit;
+        Ok(())
+    }
+
+    // Escrow LP shares as voting power. A deposit only counts for proposals created after it,
+    // so shares bought once a proposal is known cannot swing that proposal.
+    pub fn deposit_voting_shares(ctx: Context<DepositVotingShares>, amount: u64) -> Result<()> {
+        require!(amount > 0, InsuranceError::InvalidAmount);
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.owner_share_account.to_account_info(),
+                to: ctx.accounts.governance_escrow.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, amount)?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let governance = &mut ctx.accounts.governance;
+        // Any proposal created before a deposit older than this has already closed voting
+        let settled_before = current_time
+            .checked_sub(governance.voting_delay)
+            .and_then(|t| t.checked_sub(governance.voting_period))
+            .ok_or(InsuranceError::MathOverflow)?;
+        ctx.accounts.voter_deposit.record_deposit(amount, current_time, settled_before)?;
+
+        governance.total_deposited = governance
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(InsuranceError::MathOverflow)?;
+        if governance.recent_deposit_at == current_time {
+            governance.recent_deposits += amount;
+        } else {
+            governance.recent_deposits = amount;
+            governance.recent_deposit_at = current_time;
+        }
+        Ok(())
+    }
+
+    // Return escrowed LP shares once every proposal the voter took part in has closed
+    pub fn withdraw_voting_shares(ctx: Context<WithdrawVotingShares>, amount: u64) -> Result<()> {
+        ctx.accounts.insurance_pool.require_not_paused(PAUSE_UNSTAKING)?;
+
+        require!(amount > 0, InsuranceError::InvalidAmount);
+        let deposit = &mut ctx.accounts.voter_deposit;
+        require!(amount <= deposit.shares, InsuranceError::InvalidAmount);
+        require!(
+            Clock::get()?.unix_timestamp >= deposit.locked_until,
+            InsuranceError::VotingSharesLocked
+        );
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.governance_escrow,
+            &ctx.accounts.owner_share_account,
+            &ctx.accounts.pool_authority,
+            ctx.accounts.insurance_pool.pool_authority_bump,
+            amount,
+        )?;
+
+        let governance = &mut ctx.accounts.governance;
+        let from_recent = deposit.remove_shares(amount, governance.recent_deposit_at);
+        governance.total_deposited -= amount;
+        governance.recent_deposits = governance.recent_deposits.saturating_sub(from_recent);
+        Ok(())
+    }
+
+    // Open a proposal; the description itself lives off chain and is committed to by its hash
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        description_hash: [u8; 32],
+        action: ProposalAction,
+    ) -> Result<()> {
+        // Variable-length payloads must fit the space reserved for the proposal
+        if let ProposalAction::AddProduct { config } = &action {
+            require!(
+                config.name.len() <= Product::MAX_NAME_LEN && config.term_options.len() <= Product::MAX_TERM_OPTIONS,
+                InsuranceError::InvalidProductConfig
+            );
+        }
+
+        // The voting window follows from the governance config
+        let governance = &mut ctx.accounts.governance;
+        let current_time = Clock::get()?.unix_timestamp;
+        let voting_start = current_time
+            .checked_add(governance.voting_delay)
+            .ok_or(InsuranceError::MathOverflow)?;
+        let voting_end = voting_start
+            .checked_add(governance.voting_period)
+            .ok_or(InsuranceError::MathOverflow)?;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.governance = governance.key();
+        proposal.id = governance.total_proposals;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.description_hash = description_hash;
+        proposal.created_at = current_time;
+        proposal.voting_start = voting_start;
+        proposal.voting_end = voting_end;
+        proposal.yes_votes = 0;
+        proposal.no_votes = 0;
+        proposal.status = ProposalStatus::Draft;
+        proposal.total_voting_power = governance.eligible_voting_power(current_time); // Snapshot of eligible shares
+        proposal.action = action;
+        proposal.eta = 0;
+
+        governance.total_proposals += 1;
+        Ok(())
+    }
+
+    // Vote on a proposal while its voting window is open, weighted by the voter's escrowed shares.
+    // The shares stay locked until voting ends so they cannot be moved to another wallet and reused.
+    pub fn submit_governance_vote(ctx: Context<SubmitVote>, vote: bool) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let current_time = Clock::get()?.unix_timestamp;
+        proposal.open_for_voting(current_time)?;
+
+        let deposit = &mut ctx.accounts.voter_deposit;
+        let weight = deposit.voting_weight(proposal);
+        require!(weight > 0, InsuranceError::NoVotingPower);
+        deposit.locked_until = deposit.locked_until.max(proposal.voting_end);
+
+        let vote_record = &mut ctx.accounts.vote_record;
+        vote_record.user = *ctx.accounts.user.key;
+        vote_record.proposal = proposal.key();
+        vote_record.vote = vote;
+        vote_record.timestamp = current_time;
+        vote_record.bump = ctx.bumps.vote_record;
+        vote_record.weight = weight;
+
+        if vote {
+            proposal.yes_votes += weight;
+        } else {
+            proposal.no_votes += weight;
+        }
+
+        Ok(())
+    }
+
+    // Settle a proposal once voting has closed. It succeeds only if the votes cast reach the quorum
+    // share of the voting power snapshotted at creation and the yes share reaches the threshold.
+    pub fn finalize_proposal(ctx: Context<FinalizeProposal>) -> Result<()> {
+        let governance = &ctx.accounts.governance;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(
+            proposal.status == ProposalStatus::Draft || proposal.status == ProposalStatus::Active,
+            InsuranceError::InvalidProposalStatus
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= proposal.voting_end,
+            InsuranceError::VotingNotEnded
+        );
+
+        let bps = BPS_DENOMINATOR as u128;
+        let votes_cast = proposal.yes_votes as u128 + proposal.no_votes as u128;
+        let quorum_met = votes_cast > 0
+            && votes_cast * bps >= proposal.total_voting_power as u128 * governance.quorum_bps as u128;
+        let threshold_met = proposal.yes_votes as u128 * bps >= votes_cast * governance.threshold_bps as u128;
+
+        proposal.status = if quorum_met && threshold_met {
+            ProposalStatus::Succeeded
+        } else {
+            ProposalStatus::Defeated
+        };
+        Ok(())
+    }
+
+    // Queue a successful proposal; it can be executed once the governance execution delay has passed
+    pub fn queue_proposal(ctx: Context<QueueProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(
+            proposal.status == ProposalStatus::Succeeded,
+            InsuranceError::InvalidProposalStatus
+        );
+
+        proposal.eta = Clock::get()?
+            .unix_timestamp
+            .checked_add(ctx.accounts.governance.execution_delay)
+            .ok_or(InsuranceError::MathOverflow)?;
+        proposal.status = ProposalStatus::Queued;
+        Ok(())
+    }
+
+    // Apply a queued proposal's payload to its pool. Anyone may crank it after the timelock; the
+    // governance PDA is the authority, so no admin or role key is involved.
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(
+            proposal.status == ProposalStatus::Queued,
+            InsuranceError::InvalidProposalStatus
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= proposal.eta,
+            InsuranceError::ProposalTimelocked
+        );
+
+        let pool = &mut ctx.accounts.insurance_pool;
+        match proposal.action.clone() {
+            ProposalAction::SetPremiumRate { product_id, annual_rate_bps } => {
+                let product = target_product(&mut ctx.accounts.product, product_id)?;
+                let mut config = product.config();
+                config.annual_rate_bps = annual_rate_bps;
+                validate_product_config(pool, &config)?;
+                product.apply_config(config);
+            }
+            ProposalAction::SetPauseFlags { flags } => {
+                require!(flags & !PAUSE_ALL == 0, InsuranceError::InvalidPauseFlags);
+                pool.pause_flags = flags;
+            }
+            ProposalAction::SetCooldowns { unstake_cooldown, grace_period, withdrawal_delay } => {
+                require!(
+                    unstake_cooldown >= 0 && grace_period >= 0 && withdrawal_delay >= 0,
+                    InsuranceError::InvalidDuration
+                );
+                pool.unstake_cooldown = unstake_cooldown;
+                pool.grace_period = grace_period;
+                pool.withdrawal_delay = withdrawal_delay;
+            }
+            ProposalAction::AddProduct { config } => {
+                let product = ctx
+                    .accounts
+                    .new_product
+                    .as_mut()
+                    .ok_or(InsuranceError::MissingProposalAccount)?;
+                add_product(pool, product, config)?;
+            }
+            ProposalAction::RotateRole { role, holder } => {
+                require!(holder != Pubkey::default(), InsuranceError::InvalidRoleHolder);
+                pool.set_role(role, holder);
+            }
+            ProposalAction::SetCapacityLimits { product_id, capacity, max_coverage_per_policy } => {
+                let product = target_product(&mut ctx.accounts.product, product_id)?;
+                require!(capacity >= product.active_coverage, InsuranceError::InvalidProductConfig);
+                let mut config = product.config();
+                config.capacity = capacity;
+                config.max_coverage_per_policy = max_coverage_per_policy;
+                validate_product_config(pool, &config)?;
+                product.apply_config(config);
+            }
+        }
+
+        proposal.status = ProposalStatus::Executed;
+        Ok(())
+    }
+
+    // Switch an existing vote while the voting window is still open
+    pub fn change_vote(ctx: Context<ChangeVote>, vote: bool) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let current_time = Clock::get()?.unix_timestamp;
+        proposal.open_for_voting(current_time)?;
+
+        let vote_record = &mut ctx.accounts.vote_record;
+        if vote_record.vote != vote {
+            let weight = vote_record.weight;
+            if vote {
+                proposal.no_votes -= weight;
+                proposal.yes_votes += weight;
+            } else {
+                proposal.yes_votes -= weight;
+                proposal.no_votes += weight;
+            }
+            vote_record.vote = vote;
+        }
+        vote_record.timestamp = current_time;
+
+        Ok(())
+    }
+}
+
+// Move tokens out of the pool vault, signed by the pool_authority PDA
+fn transfer_from_vault<'info>(
+    token_program: &Program<'info, Token>,
+    vault: &Account<'info, TokenAccount>,
+    destination: &Account<'info, TokenAccount>,
+    pool_authority: &AccountInfo<'info>,
+    pool_authority_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let seeds: &[&[u8]] = &[b"insurance_pool".as_ref(), &[pool_authority_bump]];
+    let signer_seeds = &[seeds];
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        token::Transfer {
+            from: vault.to_account_info(),
+            to: destination.to_account_info(),
+            authority: pool_authority.clone(),
+        },
+        signer_seeds,
+    );
+    token::transfer(cpi_ctx, amount)
+}
+
+// Check a policy can be renewed now and quote the premium for its next term
+fn quote_renewal(
+    policy: &InsurancePolicy,
+    product: &Product,
+    pool: &InsurancePool,
+    capital: u64,
+    current_time: i64,
+) -> Result<u64> {
+    require!(policy.is_active, InsuranceError::PolicyNotActive);
+    require!(policy.renewed_term_start == 0, InsuranceError::AlreadyRenewed);
+    require!(
+        policy.installments_paid == policy.installment_count,
+        InsuranceError::PremiumNotPaid
+    );
+    require!(product.enabled, InsuranceError::ProductDisabled);
+    require!(product.term_options.contains(&policy.term), InsuranceError::InvalidDuration);
+    require!(
+        current_time >= policy.end_time - product.renewal_window && current_time <= policy.end_time,
+        InsuranceError::OutsideRenewalWindow
+    );
+
+    // Coverage already paid out this term is reinstated for the next one
+    require!(
+        product.active_coverage + policy.claimed_to_date <= product.capacity,
+        InsuranceError::ProductCapacityExceeded
+    );
+    quote_premium(
+        product,
+        pool,
+        capital,
+        policy.coverage_amount,
+        policy.claimed_to_date,
+        policy.term,
+    )
+}
+
+// Extend a policy by its next term; the current term keeps running and its claims history and
+// claim counter carry over. Coverage paid out so far is reserved again for the next term.
+fn apply_renewal(
+    policy: &mut InsurancePolicy,
+    product: &mut Product,
+    pool: &mut InsurancePool,
+    premium_amount: u64,
+) -> Result<()> {
+    pool.total_premium_collected += premium_amount;
+    reserve_coverage(pool, product, policy.claimed_to_date);
+
+    policy.renewed_term_start = policy.end_time;
+    policy.end_time = policy.end_time.checked_add(policy.term).ok_or(InsuranceError::MathOverflow)?;
+    policy.premium_amount = premium_amount;
+    policy.installment_count = 1;
+    policy.installments_paid = 1;
+    policy.installment_period = policy.term;
+    policy.paid_through = policy.end_time;
+    policy.premium_paid += premium_amount;
+    policy.renewal_count += 1;
+    Ok(())
+}
+
+// Once a renewed term has begun, coverage paid out in the previous term is available again;
+// its capital was reserved at renewal
+fn start_renewed_term(policy: &mut InsurancePolicy, current_time: i64) {
+    if policy.is_active && policy.renewed_term_start > 0 && current_time >= policy.renewed_term_start {
+        policy.claimed_to_date = 0;
+        policy.renewed_term_start = 0;
+    }
+}
+
+// Take a policy out of force and drop it from the holder's active count for its product
+fn close_policy(policy: &mut InsurancePolicy, holding: &mut ProductHolding) {
+    policy.is_active = false;
+    holding.active_policies = holding.active_policies.saturating_sub(1);
+}
+
+// Commit pool capital and product capacity to newly written coverage
+fn reserve_coverage(pool: &mut InsurancePool, product: &mut Product, amount: u64) {
+    pool.active_coverage += amount;
+    product.active_coverage += amount;
+}
+
+// Free up capital and capacity once coverage is paid out or no longer in force
+fn release_coverage(pool: &mut InsurancePool, product: &mut Product, amount: u64) {
+    pool.active_coverage = pool.active_coverage.saturating_sub(amount);
+    product.active_coverage = product.active_coverage.saturating_sub(amount);
+}
+
+// Register a new product under the pool's next product id
+fn add_product(
+    pool: &mut Account<InsurancePool>,
+    product: &mut Account<Product>,
+    config: ProductConfig,
+) -> Result<()> {
+    validate_product_config(pool, &config)?;
+
+    product.pool = pool.key();
+    product.id = pool.product_count;
+    product.active_coverage = 0;
+    product.apply_config(config);
+
+    pool.product_count += 1;
+    Ok(())
+}
+
+// The product a proposal targets, which must be passed to execute_proposal
+fn target_product<'a, 'info>(
+    product: &'a mut Option<Account<'info, Product>>,
+    product_id: u64,
+) -> Result<&'a mut Account<'info, Product>> {
+    let product = product.as_mut().ok_or(InsuranceError::MissingProposalAccount)?;
+    require!(product.id == product_id, InsuranceError::InvalidProduct);
+    Ok(product)
+}
+
+// Governance rules must describe a reachable outcome and a non-empty voting window
+fn validate_governance_config(config: &GovernanceConfig) -> Result<()> {
+    require!(
+        config.quorum_bps as u64 <= BPS_DENOMINATOR,
+        InsuranceError::InvalidGovernanceConfig
+    );
+    require!(
+        config.threshold_bps > 0 && config.threshold_bps as u64 <= BPS_DENOMINATOR,
+        InsuranceError::InvalidGovernanceConfig
+    );
+    require!(config.voting_delay >= 0, InsuranceError::InvalidDuration);
+    require!(config.voting_period > 0, InsuranceError::InvalidDuration);
+    require!(config.execution_delay >= 0, InsuranceError::InvalidDuration);
+    Ok(())
+}
+
+// Check a product's parameters against the pool's limits
+fn validate_product_config(pool: &InsurancePool, config: &ProductConfig) -> Result<()> {
+    require!(config.name.len() <= Product::MAX_NAME_LEN, InsuranceError::InvalidProductConfig);
+    require!(
+        !config.term_options.is_empty() && config.term_options.len() <= Product::MAX_TERM_OPTIONS,
+        InsuranceError::InvalidProductConfig
+    );
+    for term in config.term_options.iter() {
+        require!(
+            *term >= pool.min_policy_term && *term <= pool.max_policy_term,
+            InsuranceError::InvalidDuration
+        );
+    }
+    require!(
+        config.coinsurance_bps as u64 <= BPS_DENOMINATOR,
+        InsuranceError::InvalidProductConfig
+    );
+    require!(
+        config.max_coverage_per_policy > 0 && config.max_coverage_per_policy <= config.capacity,
+        InsuranceError::InvalidProductConfig
+    );
+    require!(config.max_installments >= 1, InsuranceError::InvalidProductConfig);
+    require!(config.max_active_policies > 0, InsuranceError::InvalidProductConfig);
+    // A window shorter than every term allows at most one renewal per term
+    require!(
+        config.renewal_window >= 0 && config.term_options.iter().all(|term| config.renewal_window < *term),
+        InsuranceError::InvalidDuration
+    );
+    Ok(())
+}
+
+// Premium for coverage: the product's annual rate pro-rated to the term, scaled by how much of
+// the pool's capital would be committed once the new commitment is reserved
+fn quote_premium(
+    product: &Product,
+    pool: &InsurancePool,
+    capital: u64,
+    coverage_amount: u64,
+    new_commitment: u64,
+    term: i64,
+) -> Result<u64> {
+    require!(capital > 0, InsuranceError::InsufficientFreeCapital);
+    let committed = pool.active_coverage as u128 + new_commitment as u128;
+    require!(committed <= capital as u128, InsuranceError::InsufficientFreeCapital);
+
+    let utilization_bps = (committed * BPS_DENOMINATOR as u128 / capital as u128) as u64;
+    let premium = product.base_premium(coverage_amount, term)? as u128
+        * utilization_multiplier_bps(utilization_bps) as u128
+        / BPS_DENOMINATOR as u128;
+    u64::try_from(premium).map_err(|_| error!(InsuranceError::MathOverflow))
+}
+
+// Piecewise-linear multiplier: 1x at zero utilization, 1.5x at the kink, 4.5x when fully utilized
+fn utilization_multiplier_bps(utilization_bps: u64) -> u64 {
+    if utilization_bps <= UTILIZATION_KINK_BPS {
+        BPS_DENOMINATOR + utilization_bps * (MULTIPLIER_AT_KINK_BPS - BPS_DENOMINATOR) / UTILIZATION_KINK_BPS
+    } else {
+        MULTIPLIER_AT_KINK_BPS
+            + (utilization_bps - UTILIZATION_KINK_BPS) * (MULTIPLIER_AT_FULL_BPS - MULTIPLIER_AT_KINK_BPS)
+                / (BPS_DENOMINATOR - UTILIZATION_KINK_BPS)
+    }
+}
+
+// Owners must be unique and the threshold reachable
+fn validate_owners(owners: &[Pubkey], threshold: u64) -> Result<()> {
+    require!(
+        !owners.is_empty() && owners.len() <= Multisig::MAX_OWNERS,
+        InsuranceError::InvalidOwners
+    );
+    for (i, owner) in owners.iter().enumerate() {
+        require!(!owners[..i].contains(owner), InsuranceError::InvalidOwners);
+    }
+    require!(
+        threshold > 0 && threshold <= owners.len() as u64,
+        InsuranceError::InvalidThreshold
+    );
+    Ok(())
+}
+
+// Capital not reserved against active coverage
+fn free_capital(pool: &InsurancePool, nav: u64) -> u64 {
+    nav.saturating_sub(pool.active_coverage)
+}
+
+// LP shares minted for a deposit; an empty pool prices shares 1:1
+fn shares_for_deposit(amount: u64, nav: u64, share_supply: u64) -> Result<u64> {
+    if share_supply == 0 {
+        return Ok(amount);
+    }
+    require!(nav > 0, InsuranceError::PoolInsolvent);
+    let shares = amount as u128 * share_supply as u128 / nav as u128;
+    u64::try_from(shares).map_err(|_| error!(InsuranceError::MathOverflow))
+}
+
+// Underlying tokens redeemable for a number of LP shares
+fn assets_for_shares(shares: u64, nav: u64, share_supply: u64) -> Result<u64> {
+    require!(shares <= share_supply, InsuranceError::InvalidAmount);
+    let amount = shares as u128 * nav as u128 / share_supply as u128;
+    u64::try_from(amount).map_err(|_| error!(InsuranceError::MathOverflow))
+}
+
+// Define the structure for an insurance policy
+#[account]
+pub struct InsurancePolicy {
+    pub user: Pubkey,
+    pub pool: Pubkey, // Pool the policy was bought from
+    pub index: u64, // Position in the holder's UserProfile, used to derive the policy PDA
+    pub product: Pubkey,
+    pub coverage_amount: u64,
+    pub premium_amount: u64,
+    pub deductible: u64,      // Deducted from every assessed loss
+    pub coinsurance_bps: u16, // Share of each loss above the deductible borne by the policyholder
+    pub per_claim_limit: u64, // Maximum payout for a single claim
+    pub start_time: i64,
+    pub end_time: i64,
+    pub is_active: bool,
+    pub claim_count: u64, // Number of claims filed, used to derive Claim PDAs
+    pub claimed_to_date: u64, // Total paid out across all claims
+    pub open_claims: u32,     // Claims submitted but not yet resolved
+    pub installment_count: u8, // 1 when the premium is paid upfront
+    pub installments_paid: u8,
+    pub installment_period: i64,
+    pub paid_through: i64,     // Cover is in force up to this time
+    pub premium_paid: u64,
+    pub lapsed: bool,
+    pub term: i64, // Length of each policy term, in seconds
+    pub auto_renew: bool,
+    pub auto_renew_max_premium: u64, // Highest premium the renewal crank may charge
+    pub renewal_count: u32,
+    pub renewed_term_start: i64, // Start of a renewed term that has not begun yet, 0 if none
+}
+
+// Define the structure for the insurance pool; funds are custodied in its token vault
+#[account]
+pub struct InsurancePool {
+    pub total_premium_collected: u64,
+    pub total_claims_paid: u64,
+    pub authority: Pubkey, // Admin: manages products and roles
+    pub claims_assessor: Pubkey, // Reviews, approves and rejects claims
+    pub treasurer: Pubkey,       // Withdraws premium funds
+    pub pauser: Pubkey,          // Halts pool operations in an emergency
+    pub pending_authority: Pubkey, // Nominated admin awaiting acceptance, default if none
+    pub pause_flags: u8, // Bitmask of PAUSE_* flags currently in force
+    pub multisig_signer: Pubkey, // Multisig signer PDA that approves large claims
+    pub large_claim_threshold: u64, // Payouts above this need the multisig; 0 disables
+    pub mint: Pubkey,      // Token the pool is denominated in
+    pub vault: Pubkey,     // PDA token account owned by pool_authority
+    pub lp_mint: Pubkey,   // Share mint for capital providers
+    pub share_escrow: Pubkey, // Holds shares queued for withdrawal
+    pub pool_authority_bump: u8,
+    pub vault_bump: u8,
+    pub lp_mint_bump: u8,
+    pub active_coverage: u64, // Coverage of active policies, reserved out of the vault
+    pub unstake_cooldown: i64,
+    pub next_unstake_ticket: u64,
+    pub unstake_queue_head: u64, // Oldest unstake request still waiting to be paid
+    pub pending_unstake_shares: u64,
+    pub min_policy_term: i64, // Bounds on the policy term, in seconds
+    pub max_policy_term: i64,
+    pub product_count: u64,
+    pub grace_period: i64, // How long an installment may be overdue before the policy lapses
+    pub withdrawal_delay: i64, // Minimum time between queueing and paying a treasury withdrawal
+    pub next_withdrawal_id: u64,
+}
+
+impl InsurancePolicy {
+    const LEN: usize = 8 + 32 + 32 + 8 + 32 + 8 + 8 + 8 + 2 + 8 + 8 + 8 + 1 + 8 + 8 + 4 + 1 + 1 + 8 + 8 + 8 + 1 + 8 + 1 + 8 + 4 + 8;
+
+    // Coverage still available for future claims
+    pub fn remaining_coverage(&self) -> u64 {
+        self.coverage_amount.saturating_sub(self.claimed_to_date)
+    }
+
+    // Coverage the pool holds capital against; all of it while a renewed term is pending
+    pub fn reserved_coverage(&self) -> u64 {
+        if self.renewed_term_start > 0 {
+            self.coverage_amount
+        } else {
+            self.remaining_coverage()
+        }
+    }
+
+    // Amount the pool owes on a loss after deductible, co-insurance and limits
+    pub fn net_payable(&self, loss: u64) -> u64 {
+        let above_deductible = loss.saturating_sub(self.deductible);
+        let insured_share = (above_deductible as u128
+            * (BPS_DENOMINATOR - self.coinsurance_bps as u64) as u128
+            / BPS_DENOMINATOR as u128) as u64;
+        insured_share
+            .min(self.per_claim_limit)
+            .min(self.remaining_coverage())
+    }
+
+    // Installments are equal, with the final one picking up any rounding remainder
+    pub fn next_installment_amount(&self) -> u64 {
+        let per_installment = self.premium_amount / self.installment_count as u64;
+        if self.installments_paid + 1 == self.installment_count {
+            self.premium_amount - per_installment * self.installments_paid as u64
+        } else {
+            per_installment
+        }
+    }
+
+    // Credit an installment and extend paid-up cover by one period
+    fn record_installment(&mut self, amount: u64) {
+        self.premium_paid += amount;
+        self.installments_paid += 1;
+        self.paid_through = if self.installments_paid == self.installment_count {
+            self.end_time
+        } else {
+            self.paid_through + self.installment_period
+        };
+    }
+}
+
+// A claim filed against a policy, one PDA per policy and claim index
+#[account]
+pub struct Claim {
+    pub policy: Pubkey,
+    pub claimant: Pubkey,
+    pub index: u64,
+    pub requested_amount: u64,
+    pub assessed_loss: u64,
+    pub approved_amount: u64,
+    pub evidence_hash: [u8; 32],
+    pub evidence_uri: String,
+    pub submitted_at: i64,
+    pub resolved_at: i64,
+    pub status: ClaimStatus,
+}
+
+impl Claim {
+    const MAX_URI_LEN: usize = 200;
+    const LEN: usize = 32 + 32 + 8 + 8 + 8 + 8 + 32 + (4 + Self::MAX_URI_LEN) + 8 + 8 + 1;
+}
+
+// Claim lifecycle: Submitted -> UnderReview -> Paid/Rejected; approval pays out in the same instruction
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ClaimStatus {
+    Submitted,
+    UnderReview,
+    Rejected,
+    Paid,
+}
+
+impl InsurancePool {
+    const LEN: usize = 8 + 8 + 32 + 32 + 32 + 32 + 32 + 32 + 32 + 32 + 32 + 1 + 32 + 8 + 1 + 1 + 1 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8;
+
+    pub fn require_not_paused(&self, flag: u8) -> Result<()> {
+        require!(self.pause_flags & flag == 0, InsuranceError::Paused);
+        Ok(())
+    }
+
+    pub fn set_role(&mut self, role: Role, holder: Pubkey) {
+        match role {
+            Role::ClaimsAssessor => self.claims_assessor = holder,
+            Role::Treasurer => self.treasurer = holder,
+            Role::Pauser => self.pauser = holder,
+        }
+    }
+}
+
+// Delegated pool roles; the admin role is the pool authority itself
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    ClaimsAssessor,
+    Treasurer,
+    Pauser,
+}
+
+// M-of-N multisig; its signer PDA [b"multisig_signer", multisig] acts on the owners' behalf
+#[account]
+pub struct Multisig {
+    pub owners: Vec<Pubkey>,
+    pub threshold: u64,
+    pub signer_bump: u8,
+    pub owner_set_seqno: u32, // Bumped whenever the owners change, invalidating pending transactions
+    pub transaction_count: u64,
+}
+
+impl Multisig {
+    const MAX_OWNERS: usize = 10;
+    const LEN: usize = (4 + 32 * Self::MAX_OWNERS) + 8 + 1 + 4 + 8;
+
+    pub fn owner_index(&self, key: &Pubkey) -> Result<usize> {
+        self.owners
+            .iter()
+            .position(|owner| owner == key)
+            .ok_or_else(|| error!(InsuranceError::NotMultisigOwner))
+    }
+}
+
+// Instruction proposed to a multisig, with the approvals and execution recorded for audit
+#[account]
+pub struct MultisigTransaction {
+    pub multisig: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub program_id: Pubkey,
+    pub accounts: Vec<TransactionAccount>,
+    pub data: Vec<u8>,
+    pub signers: Vec<bool>, // signers[i] is true once owners[i] has approved
+    pub owner_set_seqno: u32,
+    pub created_at: i64,
+    pub did_execute: bool,
+    pub executor: Pubkey,
+    pub executed_at: i64,
+}
+
+impl MultisigTransaction {
+    pub fn space(account_count: usize, data_len: usize, owner_count: usize) -> usize {
+        32 + 8 + 32 + 32
+            + (4 + account_count * TransactionAccount::LEN)
+            + (4 + data_len)
+            + (4 + owner_count)
+            + 4 + 8 + 1 + 32 + 8
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: self
+                .accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.pubkey,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: self.data.clone(),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TransactionAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl TransactionAccount {
+    const LEN: usize = 32 + 1 + 1;
+}
+
+// Per-user index of policies bought from a pool
+#[account]
+pub struct UserProfile {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub policy_count: u64, // Policies ever bought; the next policy PDA uses this as its index
+    pub bump: u8,
+}
+
+impl UserProfile {
+    const LEN: usize = 32 + 32 + 8 + 1;
+}
+
+// A user's policies in force under one product, checked against the product's cap
+#[account]
+pub struct ProductHolding {
+    pub product: Pubkey,
+    pub user: Pubkey,
+    pub active_policies: u32,
+    pub bump: u8,
+}
+
+impl ProductHolding {
+    const LEN: usize = 32 + 32 + 4 + 1;
+}
+
+// A type of cover sold by a pool, with its own terms and capacity
+#[account]
+pub struct Product {
+    pub pool: Pubkey,
+    pub id: u64,
+    pub name: String,
+    pub term_options: Vec<i64>,       // Policy terms on offer, in seconds
+    pub annual_rate_bps: u16,         // Premium per year as a share of coverage
+    pub min_deductible: u64,
+    pub coinsurance_bps: u16,         // Applied to every policy sold under this product
+    pub max_coverage_per_policy: u64,
+    pub capacity: u64,                // Maximum coverage in force across all policies
+    pub active_coverage: u64,
+    pub enabled: bool,
+    pub max_installments: u8,         // 1 when only upfront payment is offered
+    pub renewal_window: i64,          // How long before expiry a policy may be renewed
+    pub max_active_policies: u32,     // Policies one user may have in force at once under this product
+}
+
+impl Product {
+    const MAX_NAME_LEN: usize = 32;
+    const MAX_TERM_OPTIONS: usize = 4;
+    const LEN: usize = 32 + 8 + (4 + Self::MAX_NAME_LEN) + (4 + 8 * Self::MAX_TERM_OPTIONS) + 2 + 8 + 2 + 8 + 8 + 8 + 1 + 1 + 8 + 4;
+
+    fn config(&self) -> ProductConfig {
+        ProductConfig {
+            name: self.name.clone(),
+            term_options: self.term_options.clone(),
+            annual_rate_bps: self.annual_rate_bps,
+            min_deductible: self.min_deductible,
+            coinsurance_bps: self.coinsurance_bps,
+            max_coverage_per_policy: self.max_coverage_per_policy,
+            capacity: self.capacity,
+            enabled: self.enabled,
+            max_installments: self.max_installments,
+            renewal_window: self.renewal_window,
+            max_active_policies: self.max_active_policies,
+        }
+    }
+
+    fn apply_config(&mut self, config: ProductConfig) {
+        self.name = config.name;
+        self.term_options = config.term_options;
+        self.annual_rate_bps = config.annual_rate_bps;
+        self.min_deductible = config.min_deductible;
+        self.coinsurance_bps = config.coinsurance_bps;
+        self.max_coverage_per_policy = config.max_coverage_per_policy;
+        self.capacity = config.capacity;
+        self.enabled = config.enabled;
+        self.max_installments = config.max_installments;
+        self.renewal_window = config.renewal_window;
+        self.max_active_policies = config.max_active_policies;
+    }
+
+    // Premium at the product's annual rate, pro-rated to the term and rounded up so cover is never free
+    pub fn base_premium(&self, coverage_amount: u64, term: i64) -> Result<u64> {
+        let premium = (coverage_amount as u128 * self.annual_rate_bps as u128 * term as u128)
+            .div_ceil(BPS_DENOMINATOR as u128 * SECONDS_PER_YEAR as u128);
+        u64::try_from(premium).map_err(|_| error!(InsuranceError::MathOverflow))
+    }
+}
+
+// Admin-supplied product parameters
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProductConfig {
+    pub name: String,
+    pub term_options: Vec<i64>,
+    pub annual_rate_bps: u16,
+    pub min_deductible: u64,
+    pub coinsurance_bps: u16,
+    pub max_coverage_per_policy: u64,
+    pub capacity: u64,
+    pub enabled: bool,
+    pub max_installments: u8,
+    pub renewal_window: i64,
+    pub max_active_policies: u32,
+}
+
+impl ProductConfig {
+    const MAX_LEN: usize = (4 + Product::MAX_NAME_LEN) + (4 + 8 * Product::MAX_TERM_OPTIONS) + 2 + 8 + 2 + 8 + 8 + 1 + 1 + 8 + 4;
+}
+
+// A queued withdrawal of LP shares, paid out in ticket order
+#[account]
+pub struct UnstakeRequest {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub ticket: u64,
+    pub shares: u64,
+    pub requested_at: i64,
+    pub unlock_time: i64,
+}
+
+impl UnstakeRequest {
+    const LEN: usize = 32 + 32 + 8 + 8 + 8 + 8;
+}
+
+// A timelocked treasury withdrawal, closed when paid or cancelled
+#[account]
+pub struct WithdrawalRequest {
+    pub pool: Pubkey,
+    pub id: u64,
+    pub amount: u64,
+    pub destination: Pubkey, // Token account that receives the funds
+    pub queued_by: Pubkey,   // Treasurer who queued it; refunded the rent on close
+    pub queued_at: i64,
+    pub unlock_time: i64,
+}
+
+impl WithdrawalRequest {
+    const LEN: usize = 32 + 8 + 8 + 32 + 32 + 8 + 8;
+}
 
 // Define the structure for tracking policy history
 #[account]
@@ -238,21 +1943,204 @@ pub enum PolicyAction {
 // Define governance structure and vote records
 #[account]
 pub struct Governance {
+    pub pool: Pubkey,
+    pub total_proposals: u64, // Also the id of the next proposal
+    pub bump: u8,
+    pub escrow: Pubkey,       // LP share account holding voters' deposits
+    pub total_deposited: u64, // Shares currently escrowed for voting
+    pub quorum_bps: u16,      // Share of the voting power that must vote
+    pub threshold_bps: u16,   // Share of the votes cast that must be in favour
+    pub voting_delay: i64,    // Time from creation until voting opens
+    pub voting_period: i64,   // How long voting stays open
+    pub execution_delay: i64, // Timelock between queueing and executing a proposal
+    pub recent_deposits: u64, // Shares deposited during the second recent_deposit_at
+    pub recent_deposit_at: i64,
+}
+
+impl Governance {
+    const LEN: usize = 32 + 8 + 1 + 32 + 8 + 2 + 2 + 8 + 8 + 8 + 8 + 8;
+
+    pub fn apply_config(&mut self, config: &GovernanceConfig) {
+        self.quorum_bps = config.quorum_bps;
+        self.threshold_bps = config.threshold_bps;
+        self.voting_delay = config.voting_delay;
+        self.voting_period = config.voting_period;
+        self.execution_delay = config.execution_delay;
+    }
+
+    // Escrowed shares that count for a proposal created now: as in voting_weight,
+    // shares deposited in the same second are left out
+    pub fn eligible_voting_power(&self, current_time: i64) -> u64 {
+        if self.recent_deposit_at < current_time {
+            self.total_deposited
+        } else {
+            self.total_deposited.saturating_sub(self.recent_deposits)
+        }
+    }
+}
+
+// Governance voting rules
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct GovernanceConfig {
+    pub quorum_bps: u16,
+    pub threshold_bps: u16,
+    pub voting_delay: i64,
+    pub voting_period: i64,
+    pub execution_delay: i64,
+}
+
+// LP shares a voter has escrowed with governance
+#[account]
+pub struct VoterDeposit {
+    pub governance: Pubkey,
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub pending: Vec<DepositCheckpoint>, // Recent deposits, oldest first, that some open proposal may predate
+    pub locked_until: i64,               // End of the latest voting window this deposit was used in
+    pub bump: u8,
+}
+
+impl VoterDeposit {
+    const MAX_PENDING_DEPOSITS: usize = 8;
+    const LEN: usize = 32 + 32 + 8 + (4 + DepositCheckpoint::LEN * Self::MAX_PENDING_DEPOSITS) + 8 + 1;
+
+    // Shares only count for proposals created after they were deposited
+    pub fn voting_weight(&self, proposal: &Proposal) -> u64 {
+        let too_late: u64 = self
+            .pending
+            .iter()
+            .filter(|checkpoint| checkpoint.at >= proposal.created_at)
+            .map(|checkpoint| checkpoint.shares)
+            .sum();
+        self.shares - too_late
+    }
+
+    // Add shares under their own deposit time. Checkpoints older than `settled_before` are dropped,
+    // since every proposal created before them has closed voting.
+    fn record_deposit(&mut self, amount: u64, current_time: i64, settled_before: i64) -> Result<()> {
+        self.pending.retain(|checkpoint| checkpoint.at >= settled_before);
+        match self.pending.last_mut() {
+            Some(last) if last.at == current_time => last.shares += amount,
+            _ => {
+                require!(
+                    self.pending.len() < Self::MAX_PENDING_DEPOSITS,
+                    InsuranceError::TooManyPendingDeposits
+                );
+                self.pending.push(DepositCheckpoint { at: current_time, shares: amount });
+            }
+        }
+        self.shares = self.shares.checked_add(amount).ok_or(InsuranceError::MathOverflow)?;
+        Ok(())
+    }
+
+    // Take shares from the newest deposits first so the shares that already count are kept.
+    // Returns how many came from the deposit made at `at`.
+    fn remove_shares(&mut self, amount: u64, at: i64) -> u64 {
+        let mut remaining = amount;
+        let mut taken_at = 0;
+        while remaining > 0 {
+            let Some(last) = self.pending.last_mut() else { break };
+            let taken = remaining.min(last.shares);
+            last.shares -= taken;
+            remaining -= taken;
+            if last.at == at {
+                taken_at = taken;
+            }
+            if last.shares == 0 {
+                self.pending.pop();
+            }
+        }
+        self.shares -= amount;
+        taken_at
+    }
+}
+
+// Shares a voter deposited at one time
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DepositCheckpoint {
+    pub at: i64,
+    pub shares: u64,
+}
+
+impl DepositCheckpoint {
+    const LEN: usize = 8 + 8;
+}
+
+// A single proposal with its own voting window and tally
+#[account]
+pub struct Proposal {
+    pub governance: Pubkey,
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub description_hash: [u8; 32], // Hash of the off-chain proposal text
+    pub created_at: i64,
+    pub voting_start: i64,
+    pub voting_end: i64,
     pub yes_votes: u64,
     pub no_votes: u64,
-    pub total_proposals: u64,
+    pub status: ProposalStatus,
+    pub total_voting_power: u64, // Escrowed shares when the proposal was created
+    pub action: ProposalAction,  // Change applied when the proposal is executed
+    pub eta: i64,                // Earliest execution time once queued
+}
+
+impl Proposal {
+    const LEN: usize = 32 + 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1 + 8 + ProposalAction::MAX_LEN + 8;
+
+    // Votes are accepted inside [voting_start, voting_end); the first one activates a draft
+    pub fn open_for_voting(&mut self, current_time: i64) -> Result<()> {
+        require!(
+            self.status == ProposalStatus::Draft || self.status == ProposalStatus::Active,
+            InsuranceError::InvalidProposalStatus
+        );
+        require!(
+            current_time >= self.voting_start && current_time < self.voting_end,
+            InsuranceError::VotingClosed
+        );
+        self.status = ProposalStatus::Active;
+        Ok(())
+    }
+}
+
+// Parameter change carried by a proposal
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum ProposalAction {
+    SetPremiumRate { product_id: u64, annual_rate_bps: u16 },
+    SetPauseFlags { flags: u8 },
+    SetCooldowns { unstake_cooldown: i64, grace_period: i64, withdrawal_delay: i64 },
+    AddProduct { config: ProductConfig },
+    RotateRole { role: Role, holder: Pubkey },
+    SetCapacityLimits { product_id: u64, capacity: u64, max_coverage_per_policy: u64 },
+}
+
+impl ProposalAction {
+    // AddProduct is the largest variant
+    const MAX_LEN: usize = 1 + ProductConfig::MAX_LEN;
+}
+
+// Proposal lifecycle: Draft -> Active -> Succeeded/Defeated -> Queued -> Executed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ProposalStatus {
+    Draft,
+    Active,
+    Succeeded,
+    Defeated,
+    Queued,
+    Executed,
 }
 
 #[account]
 pub struct VoteRecord {
     pub user: Pubkey,
-    pub proposal_id: u64,
+    pub proposal: Pubkey,
     pub vote: bool,
-    pub timestamp: i64,
+    pub timestamp: i64, // Time of the latest vote or change
+    pub bump: u8,
+    pub weight: u64, // Shares counted for this vote
 }
 
 impl VoteRecord {
-    const LEN: usize = 32 + 8 + 1 + 8;
+    const LEN: usize = 32 + 32 + 1 + 8 + 1 + 8;
 }
 
 // Error Handling
@@ -264,97 +2152,661 @@ pub enum InsuranceError {
     PolicyExpired,
     #[msg("The insurance pool has insufficient funds.")]
     NotEnoughFunds,
+    #[msg("The token account mint does not match the pool mint.")]
+    InvalidMint,
+    #[msg("The amount must be greater than zero.")]
+    InvalidAmount,
+    #[msg("The pool has outstanding shares but no assets.")]
+    PoolInsolvent,
+    #[msg("Arithmetic overflow.")]
+    MathOverflow,
+    #[msg("The duration must not be negative.")]
+    InvalidDuration,
+    #[msg("The unstake cooldown has not elapsed.")]
+    CooldownNotElapsed,
+    #[msg("Unstake requests must be completed in queue order.")]
+    NotQueueHead,
+    #[msg("The pool has no free capital to pay out.")]
+    InsufficientFreeCapital,
+    #[msg("The policy does not belong to this pool.")]
+    PolicyPoolMismatch,
+    #[msg("The policy has not expired yet.")]
+    PolicyNotExpired,
+    #[msg("The claim amount exceeds the policy coverage.")]
+    ClaimExceedsCoverage,
+    #[msg("The evidence URI is too long.")]
+    EvidenceUriTooLong,
+    #[msg("The claim is not in a state that allows this action.")]
+    InvalidClaimStatus,
+    #[msg("The approved amount exceeds the amount requested.")]
+    ClaimExceedsRequest,
+    #[msg("The policy has no remaining coverage.")]
+    CoverageExhausted,
+    #[msg("The policy has claims that are still being settled.")]
+    OpenClaimsPending,
+    #[msg("The policy terms are invalid.")]
+    InvalidPolicyTerms,
+    #[msg("The assessed loss does not exceed the deductible.")]
+    BelowDeductible,
+    #[msg("The start time must not be in the past or beyond the scheduling horizon.")]
+    InvalidStartTime,
+    #[msg("The policy has not started yet.")]
+    PolicyNotStarted,
+    #[msg("The product configuration is invalid.")]
+    InvalidProductConfig,
+    #[msg("The product is not currently on sale.")]
+    ProductDisabled,
+    #[msg("The product does not belong to this pool or policy.")]
+    InvalidProduct,
+    #[msg("The product has no capacity left for this coverage.")]
+    ProductCapacityExceeded,
+    #[msg("The signer does not hold the required pool role.")]
+    Unauthorized,
+    #[msg("The quoted premium exceeds the maximum the buyer accepted.")]
+    PremiumSlippageExceeded,
+    #[msg("The premium for the current period has not been paid.")]
+    PremiumNotPaid,
+    #[msg("The premium has already been paid in full.")]
+    PremiumFullyPaid,
+    #[msg("The policy is past its grace period and has lapsed.")]
+    PolicyLapsed,
+    #[msg("The policy is not overdue beyond its grace period.")]
+    PolicyNotOverdue,
+    #[msg("The policy is outside its renewal window.")]
+    OutsideRenewalWindow,
+    #[msg("Auto-renewal is not enabled for this policy.")]
+    AutoRenewDisabled,
+    #[msg("The product already has the maximum number of active policies.")]
+    TooManyActivePolicies,
+    #[msg("The signer does not own this policy.")]
+    NotPolicyOwner,
+    #[msg("A role cannot be granted to the default public key.")]
+    InvalidRoleHolder,
+    #[msg("There is no pending authority transfer.")]
+    NoPendingAuthority,
+    #[msg("This operation is paused.")]
+    Paused,
+    #[msg("Unknown pause flag bits.")]
+    InvalidPauseFlags,
+    #[msg("Multisig owners must be unique and at most ten.")]
+    InvalidOwners,
+    #[msg("The multisig threshold must be between one and the number of owners.")]
+    InvalidThreshold,
+    #[msg("The signer is not an owner of this multisig.")]
+    NotMultisigOwner,
+    #[msg("The multisig transaction has already been executed.")]
+    AlreadyExecuted,
+    #[msg("The multisig transaction does not have enough approvals.")]
+    NotEnoughApprovals,
+    #[msg("The multisig owners changed after this transaction was proposed.")]
+    OwnerSetChanged,
+    #[msg("Claims above the large-claim threshold must be approved by the multisig.")]
+    MultisigApprovalRequired,
+    #[msg("The unlock time is earlier than the pool's withdrawal delay allows.")]
+    InvalidUnlockTime,
+    #[msg("The withdrawal is still timelocked.")]
+    WithdrawalLocked,
+    #[msg("The proposal is not in the required status.")]
+    InvalidProposalStatus,
+    #[msg("Voting is not open for this proposal.")]
+    VotingClosed,
+    #[msg("No escrowed shares were eligible when this proposal was created.")]
+    NoVotingPower,
+    #[msg("Escrowed shares are locked until the proposals they voted on close.")]
+    VotingSharesLocked,
+    #[msg("Too many recent deposits are waiting to count; try again once older ones settle.")]
+    TooManyPendingDeposits,
+    #[msg("Quorum and threshold must be valid basis points.")]
+    InvalidGovernanceConfig,
+    #[msg("Voting on this proposal has not ended yet.")]
+    VotingNotEnded,
+    #[msg("The proposal's execution timelock has not passed.")]
+    ProposalTimelocked,
+    #[msg("An account required by the proposal's action was not provided.")]
+    MissingProposalAccount,
+    #[msg("Coverage can only be reduced; buy a new policy for more cover.")]
+    CoverageIncreaseNotAllowed,
+    #[msg("The policy has already been renewed for its next term.")]
+    AlreadyRenewed,
+}
+
+// Contexts for instructions
+
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(init, payer = admin, space = 8 + InsurancePool::LEN)]
+    pub insurance_pool: Account<'info, InsurancePool>,
+    /// CHECK: PDA that signs for the pool vault; holds no data
+    #[account(
+        seeds = [b"insurance_pool".as_ref()],
+        bump
+    )]
+    pub pool_authority: AccountInfo<'info>, // PDA controlling the insurance pool
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"vault".as_ref(), insurance_pool.key().as_ref(), mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = pool_authority
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"lp_mint".as_ref(), insurance_pool.key().as_ref()],
+        bump,
+        mint::decimals = mint.decimals,
+        mint::authority = pool_authority
+    )]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"share_escrow".as_ref(), insurance_pool.key().as_ref()],
+        bump,
+        token::mint = lp_mint,
+        token::authority = pool_authority
+    )]
+    pub share_escrow: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageRole<'info> {
+    #[account(mut)]
+    pub insurance_pool: Account<'info, InsurancePool>,
+    #[account(address = insurance_pool.authority @ InsuranceError::Unauthorized)]
+    pub admin: Signer<'info>, // Only admin can grant and revoke roles
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthorityTransfer<'info> {
+    #[account(mut)]
+    pub insurance_pool: Account<'info, InsurancePool>,
+    #[account(address = insurance_pool.pending_authority @ InsuranceError::Unauthorized)]
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseFlags<'info> {
+    #[account(mut)]
+    pub insurance_pool: Account<'info, InsurancePool>,
+    #[account(address = insurance_pool.pauser @ InsuranceError::Unauthorized)]
+    pub pauser: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMultisig<'info> {
+    #[account(init, payer = payer, space = 8 + Multisig::LEN)]
+    pub multisig: Account<'info, Multisig>,
+    /// CHECK: PDA that signs for the multisig; holds no data
+    #[account(seeds = [b"multisig_signer".as_ref(), multisig.key().as_ref()], bump)]
+    pub multisig_signer: AccountInfo<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(program_id: Pubkey, accounts: Vec<TransactionAccount>, data: Vec<u8>)]
+pub struct CreateMultisigTransaction<'info> {
+    #[account(mut)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + MultisigTransaction::space(accounts.len(), data.len(), multisig.owners.len()),
+        seeds = [
+            b"multisig_tx".as_ref(),
+            multisig.key().as_ref(),
+            multisig.transaction_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub transaction: Account<'info, MultisigTransaction>,
+    #[account(mut)]
+    pub proposer: Signer<'info>, // Must be one of the owners
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveMultisigTransaction<'info> {
+    pub multisig: Account<'info, Multisig>,
+    #[account(mut, has_one = multisig)]
+    pub transaction: Account<'info, MultisigTransaction>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteMultisigTransaction<'info> {
+    pub multisig: Account<'info, Multisig>,
+    /// CHECK: PDA that signs for the multisig; holds no data
+    #[account(seeds = [b"multisig_signer".as_ref(), multisig.key().as_ref()], bump = multisig.signer_bump)]
+    pub multisig_signer: AccountInfo<'info>,
+    #[account(mut, has_one = multisig)]
+    pub transaction: Account<'info, MultisigTransaction>,
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ChangeMultisigOwners<'info> {
+    #[account(mut)]
+    pub multisig: Account<'info, Multisig>,
+    #[account(seeds = [b"multisig_signer".as_ref(), multisig.key().as_ref()], bump = multisig.signer_bump)]
+    pub multisig_signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeUserProfile<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + UserProfile::LEN,
+        seeds = [b"user_profile".as_ref(), insurance_pool.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    pub insurance_pool: Account<'info, InsurancePool>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
-// Contexts for instructions
+#[derive(Accounts)]
+pub struct InitializeProductHolding<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + ProductHolding::LEN,
+        seeds = [b"product_holding".as_ref(), product.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub product_holding: Account<'info, ProductHolding>,
+    pub product: Account<'info, Product>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
 #[derive(Accounts)]
-pub struct InitializePool<'info> {
-    #[account(init, payer = admin, space = 8 + InsurancePool::LEN)]
-    pub insurance_pool: Account<'info, InsurancePool>,
+pub struct CreateProduct<'info> {
     #[account(
-        seeds = [b"insurance_pool".as_ref()], 
+        init,
+        payer = admin,
+        space = 8 + Product::LEN,
+        seeds = [
+            b"product".as_ref(),
+            insurance_pool.key().as_ref(),
+            insurance_pool.product_count.to_le_bytes().as_ref()
+        ],
         bump
     )]
-    pub pool_authority: AccountInfo<'info>, // PDA controlling the insurance pool
+    pub product: Account<'info, Product>,
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub insurance_pool: Account<'info, InsurancePool>,
+    #[account(mut, address = insurance_pool.authority @ InsuranceError::Unauthorized)]
+    pub admin: Signer<'info>, // Only admin can manage products
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct UpdateProduct<'info> {
+    #[account(mut, constraint = product.pool == insurance_pool.key() @ InsuranceError::InvalidProduct)]
+    pub product: Account<'info, Product>,
+    pub insurance_pool: Account<'info, InsurancePool>,
+    #[account(address = insurance_pool.authority @ InsuranceError::Unauthorized)]
+    pub admin: Signer<'info>, // Only admin can manage products
+}
+
 #[derive(Accounts)]
 pub struct PurchaseInsurance<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"user_profile".as_ref(), insurance_pool.key().as_ref(), user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
     #[account(
         init,
         payer = user,
-        space = 8 + InsurancePolicy::LEN
+        space = 8 + InsurancePolicy::LEN,
+        seeds = [
+            b"policy".as_ref(),
+            insurance_pool.key().as_ref(),
+            user.key().as_ref(),
+            user_profile.policy_count.to_le_bytes().as_ref()
+        ],
+        bump
     )]
     pub insurance_policy: Account<'info, InsurancePolicy>,
+    #[account(mut, constraint = product.pool == insurance_pool.key() @ InsuranceError::InvalidProduct)]
+    pub product: Account<'info, Product>,
+    #[account(
+        mut,
+        seeds = [b"product_holding".as_ref(), product.key().as_ref(), user.key().as_ref()],
+        bump = product_holding.bump
+    )]
+    pub product_holding: Account<'info, ProductHolding>,
     #[account(mut)]
     pub insurance_pool: Account<'info, InsurancePool>,
+    #[account(mut, constraint = user_token_account.mint == insurance_pool.mint @ InsuranceError::InvalidMint)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    #[account(mut, address = insurance_pool.vault)]
+    pub vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct CancelPolicy<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        has_one = user @ InsuranceError::NotPolicyOwner,
+        constraint = insurance_policy.pool == insurance_pool.key() @ InsuranceError::PolicyPoolMismatch
+    )]
     pub insurance_policy: Account<'info, InsurancePolicy>,
+    #[account(mut, address = insurance_policy.product @ InsuranceError::InvalidProduct)]
+    pub product: Account<'info, Product>,
+    #[account(
+        mut,
+        seeds = [b"product_holding".as_ref(), insurance_policy.product.as_ref(), insurance_policy.user.as_ref()],
+        bump = product_holding.bump
+    )]
+    pub product_holding: Account<'info, ProductHolding>,
     #[account(mut)]
     pub insurance_pool: Account<'info, InsurancePool>,
+    #[account(mut, address = insurance_pool.vault)]
+    pub vault: Account<'info, TokenAccount>,
+    /// CHECK: PDA that signs for the pool vault; holds no data
+    #[account(seeds = [b"insurance_pool".as_ref()], bump = insurance_pool.pool_authority_bump)]
+    pub pool_authority: AccountInfo<'info>,
+    #[account(mut, constraint = user_token_account.mint == insurance_pool.mint @ InsuranceError::InvalidMint)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitClaim<'info> {
+    #[account(
+        mut,
+        has_one = user @ InsuranceError::NotPolicyOwner,
+        constraint = insurance_policy.pool == insurance_pool.key() @ InsuranceError::PolicyPoolMismatch
+    )]
+    pub insurance_policy: Account<'info, InsurancePolicy>,
+    pub insurance_pool: Account<'info, InsurancePool>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Claim::LEN,
+        seeds = [
+            b"claim".as_ref(),
+            insurance_policy.key().as_ref(),
+            insurance_policy.claim_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub claim: Account<'info, Claim>,
+    #[account(mut)]
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct ReviewClaim<'info> {
+    #[account(mut)]
+    pub claim: Account<'info, Claim>,
+    #[account(
+        address = claim.policy,
+        constraint = insurance_policy.pool == insurance_pool.key() @ InsuranceError::PolicyPoolMismatch
+    )]
+    pub insurance_policy: Account<'info, InsurancePolicy>,
+    pub insurance_pool: Account<'info, InsurancePool>,
+    #[account(address = insurance_pool.claims_assessor @ InsuranceError::Unauthorized)]
+    pub claims_assessor: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct ApproveClaim<'info> {
     #[account(mut)]
+    pub claim: Account<'info, Claim>,
+    #[account(
+        mut,
+        address = claim.policy,
+        constraint = insurance_policy.pool == insurance_pool.key() @ InsuranceError::PolicyPoolMismatch
+    )]
+    pub insurance_policy: Account<'info, InsurancePolicy>,
+    #[account(mut, address = insurance_policy.product @ InsuranceError::InvalidProduct)]
+    pub product: Account<'info, Product>,
+    #[account(
+        mut,
+        seeds = [b"product_holding".as_ref(), insurance_policy.product.as_ref(), insurance_policy.user.as_ref()],
+        bump = product_holding.bump
+    )]
+    pub product_holding: Account<'info, ProductHolding>,
+    #[account(
+        mut,
+        constraint = policyholder_token_account.mint == insurance_pool.mint @ InsuranceError::InvalidMint,
+        constraint = policyholder_token_account.owner == insurance_policy.user
+    )]
+    pub policyholder_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub insurance_pool: Account<'info, InsurancePool>,
+    #[account(mut, address = insurance_pool.vault)]
+    pub vault: Account<'info, TokenAccount>,
+    /// CHECK: PDA that signs for the pool vault; holds no data
+    #[account(seeds = [b"insurance_pool".as_ref()], bump = insurance_pool.pool_authority_bump)]
+    pub pool_authority: AccountInfo<'info>,
+    #[account(
+        constraint = claims_assessor.key() == insurance_pool.claims_assessor
+            || claims_assessor.key() == insurance_pool.multisig_signer @ InsuranceError::Unauthorized
+    )]
+    pub claims_assessor: Signer<'info>, // The assessor, or the multisig for large claims
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RejectClaim<'info> {
+    #[account(mut)]
+    pub claim: Account<'info, Claim>,
+    #[account(
+        mut,
+        address = claim.policy,
+        constraint = insurance_policy.pool == insurance_pool.key() @ InsuranceError::PolicyPoolMismatch
+    )]
     pub insurance_policy: Account<'info, InsurancePolicy>,
+    pub insurance_pool: Account<'info, InsurancePool>,
+    #[account(address = insurance_pool.claims_assessor @ InsuranceError::Unauthorized)]
+    pub claims_assessor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QueueWithdrawal<'info> {
+    #[account(
+        init,
+        payer = treasurer,
+        space = 8 + WithdrawalRequest::LEN,
+        seeds = [
+            b"withdrawal".as_ref(),
+            insurance_pool.key().as_ref(),
+            insurance_pool.next_withdrawal_id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
     #[account(mut)]
     pub insurance_pool: Account<'info, InsurancePool>,
-    pub admin: Signer<'info>, // Only admin can approve claims
+    #[account(constraint = destination.mint == insurance_pool.mint @ InsuranceError::InvalidMint)]
+    pub destination: Account<'info, TokenAccount>,
+    #[account(mut, address = insurance_pool.treasurer @ InsuranceError::Unauthorized)]
+    pub treasurer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct CancelWithdrawal<'info> {
+    #[account(
+        mut,
+        has_one = queued_by,
+        constraint = withdrawal_request.pool == insurance_pool.key(),
+        close = queued_by
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+    pub insurance_pool: Account<'info, InsurancePool>,
+    /// CHECK: receives the request's rent; checked against withdrawal_request.queued_by
+    #[account(mut)]
+    pub queued_by: AccountInfo<'info>,
+    #[account(address = insurance_pool.pauser @ InsuranceError::Unauthorized)]
+    pub pauser: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct WithdrawPremium<'info> {
+    #[account(
+        mut,
+        has_one = destination,
+        has_one = queued_by,
+        constraint = withdrawal_request.pool == insurance_pool.key(),
+        close = queued_by
+    )]
+    pub withdrawal_request: Account<'info, WithdrawalRequest>,
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+    /// CHECK: receives the request's rent; checked against withdrawal_request.queued_by
     #[account(mut)]
-    pub admin: Signer<'info>,  // Admin account (needs to be a designated admin)
+    pub queued_by: AccountInfo<'info>,
     #[account(mut)]
     pub insurance_pool: Account<'info, InsurancePool>,
-    pub system_program: Program<'info, System>,
+    #[account(mut, address = insurance_pool.vault)]
+    pub vault: Account<'info, TokenAccount>,
+    /// CHECK: PDA that signs for the pool vault; holds no data
+    #[account(seeds = [b"insurance_pool".as_ref()], bump = insurance_pool.pool_authority_bump)]
+    pub pool_authority: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
 pub struct LogPolicyAction<'info> {
     #[account(mut)]
     pub policy_history: Account<'info, PolicyHistory>,
+    #[account(has_one = user @ InsuranceError::NotPolicyOwner)]
     pub insurance_policy: Account<'info, InsurancePolicy>,
     pub user: Signer<'info>,
 }
 
 #[derive(Accounts)]
 pub struct ProcessExpiration<'info> {
+    #[account(mut, constraint = insurance_policy.pool == insurance_pool.key() @ InsuranceError::PolicyPoolMismatch)]
+    pub insurance_policy: Account<'info, InsurancePolicy>,
+    #[account(mut, address = insurance_policy.product @ InsuranceError::InvalidProduct)]
+    pub product: Account<'info, Product>,
+    #[account(
+        mut,
+        seeds = [b"product_holding".as_ref(), insurance_policy.product.as_ref(), insurance_policy.user.as_ref()],
+        bump = product_holding.bump
+    )]
+    pub product_holding: Account<'info, ProductHolding>,
     #[account(mut)]
+    pub insurance_pool: Account<'info, InsurancePool>,
+}
+
+#[derive(Accounts)]
+pub struct LapsePolicy<'info> {
+    #[account(mut, constraint = insurance_policy.pool == insurance_pool.key() @ InsuranceError::PolicyPoolMismatch)]
     pub insurance_policy: Account<'info, InsurancePolicy>,
+    #[account(mut, address = insurance_policy.product @ InsuranceError::InvalidProduct)]
+    pub product: Account<'info, Product>,
+    #[account(
+        mut,
+        seeds = [b"product_holding".as_ref(), insurance_policy.product.as_ref(), insurance_policy.user.as_ref()],
+        bump = product_holding.bump
+    )]
+    pub product_holding: Account<'info, ProductHolding>,
+    #[account(mut)]
+    pub insurance_pool: Account<'info, InsurancePool>,
 }
 
 #[derive(Accounts)]
-pub struct AdjustCoverage<'info> {
+pub struct RenewPolicy<'info> {
+    #[account(
+        mut,
+        has_one = user @ InsuranceError::NotPolicyOwner,
+        constraint = insurance_policy.pool == insurance_pool.key() @ InsuranceError::PolicyPoolMismatch
+    )]
+    pub insurance_policy: Account<'info, InsurancePolicy>,
+    #[account(mut, address = insurance_policy.product @ InsuranceError::InvalidProduct)]
+    pub product: Account<'info, Product>,
     #[account(mut)]
+    pub insurance_pool: Account<'info, InsurancePool>,
+    #[account(mut, constraint = user_token_account.mint == insurance_pool.mint @ InsuranceError::InvalidMint)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    #[account(mut, address = insurance_pool.vault)]
+    pub vault: Account<'info, TokenAccount>,
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetAutoRenew<'info> {
+    #[account(mut, has_one = user @ InsuranceError::NotPolicyOwner)]
     pub insurance_policy: Account<'info, InsurancePolicy>,
     pub user: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct PayPremiumWithToken<'info> {
+pub struct ProcessAutoRenewal<'info> {
+    #[account(mut, constraint = insurance_policy.pool == insurance_pool.key() @ InsuranceError::PolicyPoolMismatch)]
+    pub insurance_policy: Account<'info, InsurancePolicy>,
+    #[account(mut, address = insurance_policy.product @ InsuranceError::InvalidProduct)]
+    pub product: Account<'info, Product>,
+    #[account(mut)]
+    pub insurance_pool: Account<'info, InsurancePool>,
+    #[account(
+        mut,
+        constraint = user_token_account.mint == insurance_pool.mint @ InsuranceError::InvalidMint,
+        constraint = user_token_account.owner == insurance_policy.user
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    #[account(mut, address = insurance_pool.vault)]
+    pub vault: Account<'info, TokenAccount>,
+    /// CHECK: PDA approved as delegate on the policyholder's token account; holds no data
+    #[account(seeds = [b"insurance_pool".as_ref()], bump = insurance_pool.pool_authority_bump)]
+    pub pool_authority: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct AdjustCoverage<'info> {
+    #[account(
+        mut,
+        has_one = user @ InsuranceError::NotPolicyOwner,
+        constraint = insurance_policy.pool == insurance_pool.key() @ InsuranceError::PolicyPoolMismatch
+    )]
+    pub insurance_policy: Account<'info, InsurancePolicy>,
+    #[account(mut, address = insurance_policy.product @ InsuranceError::InvalidProduct)]
+    pub product: Account<'info, Product>,
     #[account(mut)]
+    pub insurance_pool: Account<'info, InsurancePool>,
     pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PayPremiumWithToken<'info> {
+    #[account(mut, constraint = insurance_policy.pool == insurance_pool.key() @ InsuranceError::PolicyPoolMismatch)]
+    pub insurance_policy: Account<'info, InsurancePolicy>,
     #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(mut, constraint = user_token_account.mint == insurance_pool.mint @ InsuranceError::InvalidMint)]
     pub user_token_account: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub pool_token_account: Account<'info, TokenAccount>,
+    #[account(mut, address = insurance_pool.vault)]
+    pub vault: Account<'info, TokenAccount>,
     #[account(mut)]
     pub insurance_pool: Account<'info, InsurancePool>,
     pub token_program: Program<'info, Token>,
@@ -364,22 +2816,261 @@ pub struct PayPremiumWithToken<'info> {
 pub struct StakeIntoPool<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
-    #[account(mut)]
+    #[account(mut, constraint = user_token_account.mint == insurance_pool.mint @ InsuranceError::InvalidMint)]
     pub user_token_account: Account<'info, TokenAccount>,
+    #[account(mut, constraint = user_share_account.mint == insurance_pool.lp_mint @ InsuranceError::InvalidMint)]
+    pub user_share_account: Account<'info, TokenAccount>,
+    #[account(mut, address = insurance_pool.vault)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(mut, address = insurance_pool.lp_mint)]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(mut, address = insurance_pool.share_escrow)]
+    pub share_escrow: Account<'info, TokenAccount>, // Receives the locked shares on the first stake
+    /// CHECK: PDA that signs for the pool vault and share mint; holds no data
+    #[account(seeds = [b"insurance_pool".as_ref()], bump = insurance_pool.pool_authority_bump)]
+    pub pool_authority: AccountInfo<'info>,
+    pub insurance_pool: Account<'info, InsurancePool>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RequestUnstake<'info> {
     #[account(mut)]
-    pub pool_token_account: Account<'info, TokenAccount>,
+    pub user: Signer<'info>,
+    #[account(mut, constraint = user_share_account.mint == insurance_pool.lp_mint @ InsuranceError::InvalidMint)]
+    pub user_share_account: Account<'info, TokenAccount>,
+    #[account(mut, address = insurance_pool.share_escrow)]
+    pub share_escrow: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + UnstakeRequest::LEN,
+        seeds = [
+            b"unstake_request".as_ref(),
+            insurance_pool.key().as_ref(),
+            insurance_pool.next_unstake_ticket.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub unstake_request: Account<'info, UnstakeRequest>,
     #[account(mut)]
     pub insurance_pool: Account<'info, InsurancePool>,
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct SubmitVote<'info> {
+pub struct CompleteUnstake<'info> {
+    #[account(mut, has_one = owner, constraint = unstake_request.pool == insurance_pool.key())]
+    pub unstake_request: Account<'info, UnstakeRequest>,
+    /// CHECK: receives the request's rent once it is fully paid; checked against the request
+    #[account(mut)]
+    pub owner: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = owner_token_account.mint == insurance_pool.mint @ InsuranceError::InvalidMint,
+        constraint = owner_token_account.owner == owner.key()
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    #[account(mut, address = insurance_pool.share_escrow)]
+    pub share_escrow: Account<'info, TokenAccount>,
+    #[account(mut, address = insurance_pool.vault)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(mut, address = insurance_pool.lp_mint)]
+    pub lp_mint: Account<'info, Mint>,
+    /// CHECK: PDA that signs for the pool vault and share mint; holds no data
+    #[account(seeds = [b"insurance_pool".as_ref()], bump = insurance_pool.pool_authority_bump)]
+    pub pool_authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub insurance_pool: Account<'info, InsurancePool>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeGovernance<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Governance::LEN,
+        seeds = [b"governance".as_ref(), insurance_pool.key().as_ref()],
+        bump
+    )]
+    pub governance: Account<'info, Governance>,
+    pub insurance_pool: Account<'info, InsurancePool>,
+    #[account(address = insurance_pool.lp_mint)]
+    pub lp_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"governance_escrow".as_ref(), governance.key().as_ref()],
+        bump,
+        token::mint = lp_mint,
+        token::authority = pool_authority
+    )]
+    pub governance_escrow: Account<'info, TokenAccount>,
+    /// CHECK: PDA that signs for the governance escrow; holds no data
+    #[account(seeds = [b"insurance_pool".as_ref()], bump = insurance_pool.pool_authority_bump)]
+    pub pool_authority: AccountInfo<'info>,
+    #[account(mut, address = insurance_pool.authority @ InsuranceError::Unauthorized)]
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateVoterDeposit<'info> {
+    pub governance: Account<'info, Governance>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + VoterDeposit::LEN,
+        seeds = [b"voter_deposit".as_ref(), governance.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub voter_deposit: Account<'info, VoterDeposit>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositVotingShares<'info> {
+    #[account(mut)]
+    pub governance: Account<'info, Governance>,
+    #[account(
+        mut,
+        seeds = [b"voter_deposit".as_ref(), governance.key().as_ref(), owner.key().as_ref()],
+        bump = voter_deposit.bump
+    )]
+    pub voter_deposit: Account<'info, VoterDeposit>,
+    #[account(mut, address = governance.escrow)]
+    pub governance_escrow: Account<'info, TokenAccount>,
+    #[account(mut, constraint = owner_share_account.mint == governance_escrow.mint @ InsuranceError::InvalidMint)]
+    pub owner_share_account: Account<'info, TokenAccount>,
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawVotingShares<'info> {
+    #[account(mut)]
+    pub governance: Account<'info, Governance>,
+    #[account(
+        mut,
+        seeds = [b"voter_deposit".as_ref(), governance.key().as_ref(), owner.key().as_ref()],
+        bump = voter_deposit.bump
+    )]
+    pub voter_deposit: Account<'info, VoterDeposit>,
+    #[account(mut, address = governance.escrow)]
+    pub governance_escrow: Account<'info, TokenAccount>,
+    #[account(mut, constraint = owner_share_account.mint == governance_escrow.mint @ InsuranceError::InvalidMint)]
+    pub owner_share_account: Account<'info, TokenAccount>,
+    #[account(address = governance.pool)]
+    pub insurance_pool: Account<'info, InsurancePool>,
+    /// CHECK: PDA that signs for the governance escrow; holds no data
+    #[account(seeds = [b"insurance_pool".as_ref()], bump = insurance_pool.pool_authority_bump)]
+    pub pool_authority: AccountInfo<'info>,
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(mut)]
+    pub governance: Account<'info, Governance>,
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + Proposal::LEN,
+        seeds = [
+            b"proposal".as_ref(),
+            governance.key().as_ref(),
+            governance.total_proposals.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
     #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeProposal<'info> {
+    pub governance: Account<'info, Governance>,
+    #[account(mut, has_one = governance)]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct QueueProposal<'info> {
     pub governance: Account<'info, Governance>,
-    #[account(init, payer = user, space = 8 + VoteRecord::LEN)]
+    #[account(mut, has_one = governance)]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(seeds = [b"governance".as_ref(), insurance_pool.key().as_ref()], bump = governance.bump)]
+    pub governance: Account<'info, Governance>,
+    #[account(mut, has_one = governance)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut)]
+    pub insurance_pool: Account<'info, InsurancePool>,
+    // Existing product targeted by SetPremiumRate or SetCapacityLimits
+    #[account(mut, constraint = product.pool == insurance_pool.key() @ InsuranceError::InvalidProduct)]
+    pub product: Option<Account<'info, Product>>,
+    // Product created by AddProduct
+    #[account(
+        init,
+        payer = executor,
+        space = 8 + Product::LEN,
+        seeds = [
+            b"product".as_ref(),
+            insurance_pool.key().as_ref(),
+            insurance_pool.product_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub new_product: Option<Account<'info, Product>>,
+    #[account(mut)]
+    pub executor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitVote<'info> {
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        mut,
+        seeds = [b"voter_deposit".as_ref(), proposal.governance.as_ref(), user.key().as_ref()],
+        bump = voter_deposit.bump
+    )]
+    pub voter_deposit: Account<'info, VoterDeposit>,
+    // One record per voter and proposal, so a second vote fails at account creation
+    #[account(
+        init,
+        payer = user,
+        space = 8 + VoteRecord::LEN,
+        seeds = [b"vote".as_ref(), proposal.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
     pub vote_record: Account<'info, VoteRecord>,
     #[account(mut)]  // Fix for the missing 'mut'
     pub user: Signer<'info>,  // Mark user as mutable (payer)
     pub system_program: Program<'info, System>,
 }
+
+#[derive(Accounts)]
+pub struct ChangeVote<'info> {
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        mut,
+        seeds = [b"vote".as_ref(), proposal.key().as_ref(), user.key().as_ref()],
+        bump = vote_record.bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    pub user: Signer<'info>,
+}
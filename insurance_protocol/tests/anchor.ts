@@ -1,6 +1,7 @@
 import * as anchor from "@coral-xyz/anchor";
 import BN from "bn.js";
 import * as web3 from "@solana/web3.js";
+import * as splToken from "@solana/spl-token";
 import type { InsuranceProtocol } from "../target/types/insurance_protocol";
 
 describe("Insurance Protocol Tests", () => {
@@ -8,22 +9,87 @@ describe("Insurance Protocol Tests", () => {
   anchor.setProvider(anchor.AnchorProvider.env());
 
   const program = anchor.workspace.InsuranceProtocol as anchor.Program<InsuranceProtocol>;
-  
-  
+  const payer = (program.provider as anchor.AnchorProvider).wallet as anchor.Wallet;
+
+  const [poolAuthority] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("insurance_pool")],
+    program.programId
+  );
+
+  // Shared pool state, populated by the initialization test
+  const poolAccountKp = new web3.Keypair();
+  let mint: web3.PublicKey;
+  let vault: web3.PublicKey;
+  let lpMint: web3.PublicKey;
+  let shareEscrow: web3.PublicKey;
+  let userProfile: web3.PublicKey;
+  let productHolding: web3.PublicKey;
+  let policyKey: web3.PublicKey;
+  let product: web3.PublicKey;
+  let userTokenAccount: web3.PublicKey;
+  let userShareAccount: web3.PublicKey;
+  let governance: web3.PublicKey;
+
+  // Policies are PDAs indexed by the holder's UserProfile counter
+  const policyAddress = (index: number) =>
+    web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("policy"),
+        poolAccountKp.publicKey.toBuffer(),
+        program.provider.publicKey.toBuffer(),
+        new BN(index).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+  // Treasury withdrawals are PDAs indexed by the pool's withdrawal counter
+  const withdrawalAddress = (id: number) =>
+    web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("withdrawal"), poolAccountKp.publicKey.toBuffer(), new BN(id).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  // Proposals are PDAs numbered by the governance account
+  const proposalAddress = (id: number) =>
+    web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("proposal"), governance.toBuffer(), new BN(id).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
   // Test for initializing the insurance pool
   it("Initialize the Insurance Pool", async () => {
-    const poolAccountKp = new web3.Keypair();
-    const poolAuthority = await web3.PublicKey.findProgramAddress(
-      [Buffer.from("insurance_pool")],
+    mint = await splToken.createMint(
+      program.provider.connection,
+      payer.payer,
+      program.provider.publicKey,
+      null,
+      6
+    );
+    [vault] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), poolAccountKp.publicKey.toBuffer(), mint.toBuffer()],
+      program.programId
+    );
+    [lpMint] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp_mint"), poolAccountKp.publicKey.toBuffer()],
       program.programId
     );
-    
+    [shareEscrow] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("share_escrow"), poolAccountKp.publicKey.toBuffer()],
+      program.programId
+    );
+
     const txHash = await program.methods
-      .initializePool(0) // bump seed
+      // no unstake cooldown; policy terms between 7 and 365 days; 3-day grace period; no treasury withdrawal delay
+      .initializePool(new BN(0), new BN(7 * 86_400), new BN(365 * 86_400), new BN(3 * 86_400), new BN(0))
       .accounts({
         insurancePool: poolAccountKp.publicKey,
-        poolAuthority: poolAuthority[0],
+        poolAuthority,
+        mint,
+        vault,
+        lpMint,
+        shareEscrow,
         admin: program.provider.publicKey,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
         systemProgram: web3.SystemProgram.programId,
       })
       .signers([poolAccountKp])
@@ -31,63 +97,731 @@ describe("Insurance Protocol Tests", () => {
 
     console.log(`Insurance Pool initialized. Tx: ${txHash}`);
     await program.provider.connection.confirmTransaction(txHash);
+
+    const pool = await program.account.insurancePool.fetch(poolAccountKp.publicKey);
+    console.log(`Pool vault: ${pool.vault.toString()}`);
+    if (!pool.vault.equals(vault)) throw new Error("Pool vault was not recorded");
+  });
+
+  // Test for adding a product to the pool's catalog
+  it("Create Product", async () => {
+    [product] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("product"), poolAccountKp.publicKey.toBuffer(), new BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    const txHash = await program.methods
+      .createProduct({
+        name: "Smart contract cover",
+        termOptions: [new BN(30 * 86_400), new BN(90 * 86_400)],
+        annualRateBps: 200,
+        minDeductible: new BN(100),
+        coinsuranceBps: 2_000,
+        maxCoveragePerPolicy: new BN(10_000),
+        capacity: new BN(100_000),
+        enabled: true,
+        maxInstallments: 3,
+        maxActivePolicies: 5,
+        renewalWindow: new BN(7 * 86_400), // must be shorter than every term
+      })
+      .accounts({
+        product,
+        insurancePool: poolAccountKp.publicKey,
+        admin: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`Product created. Tx: ${txHash}`);
+    await program.provider.connection.confirmTransaction(txHash);
+  });
+
+  // Test for delegating and revoking a pool role
+  it("Grant and Revoke Role", async () => {
+    const treasurer = web3.Keypair.generate();
+
+    await program.methods
+      .grantRole({ treasurer: {} }, treasurer.publicKey)
+      .accounts({ insurancePool: poolAccountKp.publicKey, admin: program.provider.publicKey })
+      .rpc();
+    let pool = await program.account.insurancePool.fetch(poolAccountKp.publicKey);
+    if (!pool.treasurer.equals(treasurer.publicKey)) throw new Error("Treasurer role should be granted");
+
+    await program.methods
+      .revokeRole({ treasurer: {} })
+      .accounts({ insurancePool: poolAccountKp.publicKey, admin: program.provider.publicKey })
+      .rpc();
+    pool = await program.account.insurancePool.fetch(poolAccountKp.publicKey);
+    if (!pool.treasurer.equals(web3.PublicKey.default)) throw new Error("Treasurer role should be revoked");
+
+    // Hand the role back to the admin for the remaining tests
+    await program.methods
+      .grantRole({ treasurer: {} }, program.provider.publicKey)
+      .accounts({ insurancePool: poolAccountKp.publicKey, admin: program.provider.publicKey })
+      .rpc();
+  });
+
+  // Test for handing the admin role to a new key in two steps
+  it("Transfer Pool Authority", async () => {
+    const pool = poolAccountKp.publicKey;
+    const admin = program.provider.publicKey;
+    const newAdmin = web3.Keypair.generate();
+
+    await program.methods.proposeAuthorityTransfer(newAdmin.publicKey).accounts({ insurancePool: pool, admin }).rpc();
+    await program.methods.cancelAuthorityTransfer().accounts({ insurancePool: pool, admin }).rpc();
+    let poolAccount = await program.account.insurancePool.fetch(pool);
+    if (!poolAccount.pendingAuthority.equals(web3.PublicKey.default)) throw new Error("Nomination should be cleared");
+
+    await program.methods.proposeAuthorityTransfer(newAdmin.publicKey).accounts({ insurancePool: pool, admin }).rpc();
+    await program.methods
+      .acceptAuthorityTransfer()
+      .accounts({ insurancePool: pool, newAuthority: newAdmin.publicKey })
+      .signers([newAdmin])
+      .rpc();
+    poolAccount = await program.account.insurancePool.fetch(pool);
+    if (!poolAccount.authority.equals(newAdmin.publicKey)) throw new Error("Nominee should become the admin");
+
+    // Hand the pool back to the provider wallet for the remaining tests
+    await program.methods
+      .proposeAuthorityTransfer(admin)
+      .accounts({ insurancePool: pool, admin: newAdmin.publicKey })
+      .signers([newAdmin])
+      .rpc();
+    await program.methods.acceptAuthorityTransfer().accounts({ insurancePool: pool, newAuthority: admin }).rpc();
+  });
+
+  // Test for staking into the pool and redeeming the LP shares through the withdrawal queue
+  it("Stake and Unstake", async () => {
+    const connection = program.provider.connection;
+    userTokenAccount = await splToken.createAccount(
+      connection, payer.payer, mint, program.provider.publicKey, web3.Keypair.generate()
+    );
+    userShareAccount = await splToken.createAccount(
+      connection, payer.payer, lpMint, program.provider.publicKey, web3.Keypair.generate()
+    );
+    await splToken.mintTo(connection, payer.payer, mint, userTokenAccount, payer.payer, 10_000);
+
+    await program.methods
+      .stakeIntoPool(new BN(10_000))
+      .accounts({
+        user: program.provider.publicKey,
+        userTokenAccount,
+        userShareAccount,
+        vault,
+        lpMint,
+        shareEscrow,
+        poolAuthority,
+        insurancePool: poolAccountKp.publicKey,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    // The first stake locks 1_000 of its shares in the escrow
+    const shares = await splToken.getAccount(connection, userShareAccount);
+    if (shares.amount !== BigInt(9_000)) throw new Error("First stake should mint shares 1:1 less the locked shares");
+    const locked = await splToken.getAccount(connection, shareEscrow);
+    if (locked.amount !== BigInt(1_000)) throw new Error("First stake should lock the minimum liquidity");
+
+    const pool = await program.account.insurancePool.fetch(poolAccountKp.publicKey);
+    const [unstakeRequest] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("unstake_request"),
+        poolAccountKp.publicKey.toBuffer(),
+        pool.nextUnstakeTicket.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+
+    await program.methods
+      .requestUnstake(new BN(4_000))
+      .accounts({
+        user: program.provider.publicKey,
+        userShareAccount,
+        shareEscrow,
+        unstakeRequest,
+        insurancePool: poolAccountKp.publicKey,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .completeUnstake()
+      .accounts({
+        unstakeRequest,
+        owner: program.provider.publicKey,
+        ownerTokenAccount: userTokenAccount,
+        shareEscrow,
+        vault,
+        lpMint,
+        poolAuthority,
+        insurancePool: poolAccountKp.publicKey,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    const tokens = await splToken.getAccount(connection, userTokenAccount);
+    if (tokens.amount !== BigInt(4_000)) throw new Error("Unstake should return the queued shares at par");
+  });
+
+  // Test for halting and resuming treasury withdrawals with the pause flags
+  it("Pause and Resume", async () => {
+    const pool = poolAccountKp.publicKey;
+    const pauser = program.provider.publicKey;
+    const withdrawalRequest = withdrawalAddress(0);
+    const queue = () =>
+      program.methods
+        .queueWithdrawal(new BN(1), null)
+        .accounts({
+          withdrawalRequest,
+          insurancePool: pool,
+          destination: userTokenAccount,
+          treasurer: program.provider.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    await program.methods.setPauseFlags(1 << 5).accounts({ insurancePool: pool, pauser }).rpc();
+    try {
+      await queue();
+      throw new Error("Withdrawal should fail while the treasury is paused");
+    } catch (err) {
+      if (!(err instanceof anchor.AnchorError) || err.error.errorCode.code !== "Paused") throw err;
+    }
+
+    await program.methods.setPauseFlags(0).accounts({ insurancePool: pool, pauser }).rpc();
+    await queue();
+
+    // The pauser can also veto a queued withdrawal
+    await program.methods
+      .cancelWithdrawal()
+      .accounts({ withdrawalRequest, insurancePool: pool, queuedBy: program.provider.publicKey, pauser })
+      .rpc();
+    if (await program.provider.connection.getAccountInfo(withdrawalRequest)) {
+      throw new Error("Cancelled withdrawal should be closed");
+    }
+  });
+
+  // Test for creating the user's policy index
+  it("Initialize User Profile", async () => {
+    [userProfile] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user_profile"), poolAccountKp.publicKey.toBuffer(), program.provider.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initializeUserProfile()
+      .accounts({
+        userProfile,
+        insurancePool: poolAccountKp.publicKey,
+        user: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    // The holding counts the user's policies in force under the product
+    [productHolding] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("product_holding"), product.toBuffer(), program.provider.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeProductHolding()
+      .accounts({
+        productHolding,
+        product,
+        user: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
   });
 
   // Test for purchasing insurance
   it("Purchase Insurance", async () => {
-    const policyAccountKp = new web3.Keypair();
-    const insurancePoolKey = new web3.PublicKey("<your-insurance-pool-address>");
-    
+    const insurancePoolKey = poolAccountKp.publicKey;
+    policyKey = policyAddress(0);
+
     const txHash = await program.methods
-      .purchaseInsurance(new BN(1000), new BN(100), new BN(5000)) // deposit, premium, coverage
+      // max premium, coverage, deductible, per-claim limit, 90-day term starting now, paid upfront
+      .purchaseInsurance(
+        new BN(100), new BN(5000), new BN(100), new BN(2_500), new BN(90 * 86_400), null, 1
+      )
       .accounts({
         user: program.provider.publicKey,
-        insurancePolicy: policyAccountKp.publicKey,
+        userProfile,
+        insurancePolicy: policyKey,
+        product,
+        productHolding,
         insurancePool: insurancePoolKey,
+        userTokenAccount,
+        vault,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
         systemProgram: web3.SystemProgram.programId,
       })
-      .signers([policyAccountKp])
       .rpc();
 
     console.log(`Insurance purchased. Tx: ${txHash}`);
     await program.provider.connection.confirmTransaction(txHash);
+
+    // The quoted premium is taken from the buyer at purchase
+    const policy = await program.account.insurancePolicy.fetch(policyKey);
+    const tokens = await splToken.getAccount(program.provider.connection, userTokenAccount);
+    if (tokens.amount !== BigInt(4_000 - policy.premiumAmount.toNumber())) {
+      throw new Error("Premium should be collected at purchase");
+    }
+  });
+
+  // Test for paying a policy's premium in installments
+  it("Pay Premium Installment", async () => {
+    const installmentPolicy = policyAddress(1);
+
+    await program.methods
+      .purchaseInsurance(
+        new BN(100), new BN(300), new BN(100), new BN(300), new BN(90 * 86_400), null, 3
+      )
+      .accounts({
+        user: program.provider.publicKey,
+        userProfile,
+        insurancePolicy: installmentPolicy,
+        product,
+        productHolding,
+        insurancePool: poolAccountKp.publicKey,
+        userTokenAccount,
+        vault,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const before = await program.account.insurancePolicy.fetch(installmentPolicy);
+
+    await program.methods
+      .payPremiumWithToken()
+      .accounts({
+        insurancePolicy: installmentPolicy,
+        user: program.provider.publicKey,
+        userTokenAccount,
+        vault,
+        insurancePool: poolAccountKp.publicKey,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const after = await program.account.insurancePolicy.fetch(installmentPolicy);
+    if (after.installmentsPaid !== 2) throw new Error("Installment should be credited to the policy");
+    if (!after.paidThrough.eq(before.paidThrough.add(before.installmentPeriod))) {
+      throw new Error("Paid-through date should advance by one period");
+    }
   });
 
   // Test for canceling a policy
   it("Cancel Insurance Policy", async () => {
-    const insurancePoolKey = new web3.PublicKey("<your-insurance-pool-address>");
-    const policyAccountKey = new web3.PublicKey("<your-policy-account>");
+    const connection = program.provider.connection;
+    const scheduledPolicy = policyAddress(2);
+    const startTime = Math.floor(Date.now() / 1000) + 86_400;
+
+    // Cover scheduled to start tomorrow has not been used, so it is refunded in full
+    await program.methods
+      .purchaseInsurance(
+        new BN(100), new BN(300), new BN(100), new BN(300), new BN(30 * 86_400), new BN(startTime), 1
+      )
+      .accounts({
+        user: program.provider.publicKey,
+        userProfile,
+        insurancePolicy: scheduledPolicy,
+        product,
+        productHolding,
+        insurancePool: poolAccountKp.publicKey,
+        userTokenAccount,
+        vault,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    const { premiumPaid } = await program.account.insurancePolicy.fetch(scheduledPolicy);
+    const balanceBefore = (await splToken.getAccount(connection, userTokenAccount)).amount;
 
     const txHash = await program.methods
       .cancelPolicy()
       .accounts({
-        insurancePolicy: policyAccountKey,
-        insurancePool: insurancePoolKey,
+        insurancePolicy: scheduledPolicy,
+        product,
+        productHolding,
+        insurancePool: poolAccountKp.publicKey,
+        vault,
+        poolAuthority,
+        userTokenAccount,
         user: program.provider.publicKey,
-        systemProgram: web3.SystemProgram.programId,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
       })
       .rpc();
 
     console.log(`Policy canceled. Tx: ${txHash}`);
+    await connection.confirmTransaction(txHash);
+
+    const policy = await program.account.insurancePolicy.fetch(scheduledPolicy);
+    const tokens = await splToken.getAccount(connection, userTokenAccount);
+    if (policy.isActive) throw new Error("Canceled policy should be inactive");
+    if (tokens.amount !== balanceBefore + BigInt(premiumPaid.toNumber())) {
+      throw new Error("Unstarted cover should be refunded in full");
+    }
+  });
+
+  // Test for submitting a claim against the purchased policy
+  it("Submit Claim", async () => {
+    const [claim] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("claim"), policyKey.toBuffer(), new BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    const txHash = await program.methods
+      .submitClaim(new BN(500), Array(32).fill(0), "ipfs://claim-evidence")
+      .accounts({
+        insurancePolicy: policyKey,
+        insurancePool: poolAccountKp.publicKey,
+        claim,
+        user: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log(`Claim submitted. Tx: ${txHash}`);
     await program.provider.connection.confirmTransaction(txHash);
+
+    const claimAccount = await program.account.claim.fetch(claim);
+    if (!("submitted" in claimAccount.status)) throw new Error("Claim should start as Submitted");
   });
 
   // Test for approving a claim
   it("Approve Claim", async () => {
-    const insurancePoolKey = new web3.PublicKey("<your-insurance-pool-address>");
-    const policyAccountKey = new web3.PublicKey("<your-policy-account>");
+    const [claim] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("claim"), policyKey.toBuffer(), new BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    const balanceBefore = (await splToken.getAccount(program.provider.connection, userTokenAccount)).amount;
 
     const txHash = await program.methods
-      .approveClaim()
+      .approveClaim(new BN(500)) // (500 - 100 deductible) * 80% = 320 payable
       .accounts({
-        insurancePolicy: policyAccountKey,
-        insurancePool: insurancePoolKey,
-        admin: program.provider.publicKey, // Admin account must approve
-        systemProgram: web3.SystemProgram.programId,
+        claim,
+        insurancePolicy: policyKey,
+        product,
+        productHolding,
+        policyholderTokenAccount: userTokenAccount,
+        insurancePool: poolAccountKp.publicKey,
+        vault,
+        poolAuthority,
+        claimsAssessor: program.provider.publicKey, // Initially held by the pool admin
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
       })
       .rpc();
 
     console.log(`Claim approved. Tx: ${txHash}`);
     await program.provider.connection.confirmTransaction(txHash);
+
+    const claimAccount = await program.account.claim.fetch(claim);
+    if (!("paid" in claimAccount.status)) throw new Error("Approved claim should be paid out");
+    const tokens = await splToken.getAccount(program.provider.connection, userTokenAccount);
+    const policy = await program.account.insurancePolicy.fetch(policyKey);
+    if (tokens.amount !== balanceBefore + BigInt(320)) throw new Error("Payout should land in the policyholder account");
+    if (!policy.claimedToDate.eq(new BN(320))) throw new Error("Partial claim should be tracked");
+    if (!policy.isActive) throw new Error("Policy should stay active with coverage remaining");
+  });
+
+  // Test for routing a treasury withdrawal through a 2-of-2 multisig
+  it("Multisig Withdrawal", async () => {
+    const pool = poolAccountKp.publicKey;
+    const admin = program.provider.publicKey;
+    const coOwner = web3.Keypair.generate();
+    const multisig = web3.Keypair.generate();
+    const [multisigSigner] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("multisig_signer"), multisig.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .createMultisig([admin, coOwner.publicKey], new BN(2))
+      .accounts({
+        multisig: multisig.publicKey,
+        multisigSigner,
+        payer: admin,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([multisig])
+      .rpc();
+    await program.methods
+      .grantRole({ treasurer: {} }, multisigSigner)
+      .accounts({ insurancePool: pool, admin })
+      .rpc();
+
+    // The multisig signer pays the request's rent, so it needs lamports of its own
+    await program.provider.sendAndConfirm(
+      new web3.Transaction().add(
+        web3.SystemProgram.transfer({ fromPubkey: admin, toPubkey: multisigSigner, lamports: web3.LAMPORTS_PER_SOL })
+      )
+    );
+
+    const withdrawalRequest = withdrawalAddress(1);
+    const withdrawIx = await program.methods
+      .queueWithdrawal(new BN(10), null)
+      .accounts({
+        withdrawalRequest,
+        insurancePool: pool,
+        destination: userTokenAccount,
+        treasurer: multisigSigner,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .instruction();
+    const [transaction] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("multisig_tx"), multisig.publicKey.toBuffer(), new BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    await program.methods
+      .createMultisigTransaction(withdrawIx.programId, withdrawIx.keys, withdrawIx.data)
+      .accounts({ multisig: multisig.publicKey, transaction, proposer: admin, systemProgram: web3.SystemProgram.programId })
+      .rpc();
+
+    const execute = () =>
+      program.methods
+        .executeMultisigTransaction()
+        .accounts({ multisig: multisig.publicKey, multisigSigner, transaction, executor: admin })
+        .remainingAccounts([
+          ...withdrawIx.keys.map((meta) => ({ ...meta, isSigner: false })),
+          { pubkey: program.programId, isSigner: false, isWritable: false },
+        ])
+        .rpc();
+
+    try {
+      await execute();
+      throw new Error("Execution should wait for the second approval");
+    } catch (err) {
+      if (!(err instanceof anchor.AnchorError) || err.error.errorCode.code !== "NotEnoughApprovals") throw err;
+    }
+
+    await program.methods
+      .approveMultisigTransaction()
+      .accounts({ multisig: multisig.publicKey, transaction, owner: coOwner.publicKey })
+      .signers([coOwner])
+      .rpc();
+
+    await execute();
+    const record = await program.account.multisigTransaction.fetch(transaction);
+    if (!record.didExecute || !record.executor.equals(admin)) throw new Error("Execution should be recorded");
+
+    // With no delay configured the queued withdrawal can be paid straight away
+    const balanceBefore = (await splToken.getAccount(program.provider.connection, userTokenAccount)).amount;
+    await program.methods
+      .withdrawPremium()
+      .accounts({
+        withdrawalRequest,
+        destination: userTokenAccount,
+        queuedBy: multisigSigner,
+        insurancePool: pool,
+        vault,
+        poolAuthority,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    const tokens = await splToken.getAccount(program.provider.connection, userTokenAccount);
+    if (tokens.amount !== balanceBefore + BigInt(10)) throw new Error("Queued withdrawal should be paid from the vault");
+  });
+
+  // Test for opening a proposal and voting on it
+  it("Governance Proposal", async () => {
+    [governance] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("governance"), poolAccountKp.publicKey.toBuffer()],
+      program.programId
+    );
+    const [governanceEscrow] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("governance_escrow"), governance.toBuffer()],
+      program.programId
+    );
+    const [voterDeposit] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("voter_deposit"), governance.toBuffer(), program.provider.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      // 20% quorum, simple majority, voting opens immediately and lasts 10 seconds, no execution timelock
+      .initializeGovernance({
+        quorumBps: 2_000,
+        thresholdBps: 5_000,
+        votingDelay: new BN(0),
+        votingPeriod: new BN(10),
+        executionDelay: new BN(0),
+      })
+      .accounts({
+        governance,
+        insurancePool: poolAccountKp.publicKey,
+        lpMint,
+        governanceEscrow,
+        poolAuthority,
+        admin: program.provider.publicKey,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    // Escrow LP shares as voting power before the proposal is created
+    await program.methods
+      .createVoterDeposit()
+      .accounts({
+        governance,
+        voterDeposit,
+        owner: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .depositVotingShares(new BN(1_000))
+      .accounts({
+        governance,
+        voterDeposit,
+        governanceEscrow,
+        ownerShareAccount: userShareAccount,
+        owner: program.provider.publicKey,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    await new Promise((resolve) => setTimeout(resolve, 1_500)); // Deposits must predate the proposal
+
+    const proposal = proposalAddress(0);
+    await program.methods
+      .createProposal(Array(32).fill(1), { setPauseFlags: { flags: 0 } })
+      .accounts({
+        governance,
+        proposal,
+        proposer: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const [voteRecord] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vote"), proposal.toBuffer(), program.provider.publicKey.toBuffer()],
+      program.programId
+    );
+    const vote = (inFavour: boolean) =>
+      program.methods
+        .submitGovernanceVote(inFavour)
+        .accounts({
+          proposal,
+          voterDeposit,
+          voteRecord,
+          user: program.provider.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+    await vote(true);
+
+    let proposalAccount = await program.account.proposal.fetch(proposal);
+    if (!("active" in proposalAccount.status)) throw new Error("First vote should activate the proposal");
+    if (!proposalAccount.yesVotes.eq(new BN(1_000))) throw new Error("Vote should carry the escrowed shares");
+
+    // A second vote from the same wallet cannot create another record
+    try {
+      await vote(true);
+      throw new Error("Duplicate vote should be rejected");
+    } catch (err) {
+      if (err instanceof Error && err.message === "Duplicate vote should be rejected") throw err;
+    }
+
+    await program.methods
+      .changeVote(false)
+      .accounts({ proposal, voteRecord, user: program.provider.publicKey })
+      .rpc();
+    proposalAccount = await program.account.proposal.fetch(proposal);
+    if (!proposalAccount.yesVotes.isZero() || !proposalAccount.noVotes.eq(new BN(1_000))) {
+      throw new Error("Changing the vote should move it between tallies");
+    }
+    const governanceAccount = await program.account.governance.fetch(governance);
+    if (!governanceAccount.totalProposals.eq(new BN(1))) throw new Error("Proposal count should increase");
+
+    // Once voting closes the outcome follows from the quorum and threshold
+    const waitMs = (proposalAccount.votingEnd.toNumber() + 1) * 1000 - Date.now();
+    await new Promise((resolve) => setTimeout(resolve, Math.max(waitMs, 0)));
+    await program.methods.finalizeProposal().accounts({ governance, proposal }).rpc();
+    proposalAccount = await program.account.proposal.fetch(proposal);
+    if (!("defeated" in proposalAccount.status)) throw new Error("A proposal voted down should be defeated");
+  });
+
+  // Test for passing a proposal and applying its payload to the pool
+  it("Execute Governance Proposal", async () => {
+    const proposal = proposalAddress(1);
+    const [voterDeposit] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("voter_deposit"), governance.toBuffer(), program.provider.publicKey.toBuffer()],
+      program.programId
+    );
+    const [voteRecord] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vote"), proposal.toBuffer(), program.provider.publicKey.toBuffer()],
+      program.programId
+    );
+    const [governanceEscrow] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("governance_escrow"), governance.toBuffer()],
+      program.programId
+    );
+    const deposit = (amount: number) =>
+      program.methods
+        .depositVotingShares(new BN(amount))
+        .accounts({
+          governance,
+          voterDeposit,
+          governanceEscrow,
+          ownerShareAccount: userShareAccount,
+          owner: program.provider.publicKey,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    // A deposit made just before the proposal counts for it
+    await deposit(200);
+    await new Promise((resolve) => setTimeout(resolve, 1_500));
+
+    await program.methods
+      .createProposal(Array(32).fill(2), { setPremiumRate: { productId: new BN(0), annualRateBps: 300 } })
+      .accounts({
+        governance,
+        proposal,
+        proposer: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    // Topping up while the proposal is open neither counts for it nor removes the earlier shares
+    await deposit(500);
+    await program.methods
+      .submitGovernanceVote(true)
+      .accounts({
+        proposal,
+        voterDeposit,
+        voteRecord,
+        user: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const { votingEnd, yesVotes, totalVotingPower } = await program.account.proposal.fetch(proposal);
+    if (!yesVotes.eq(new BN(1_200)) || !totalVotingPower.eq(new BN(1_200))) {
+      throw new Error("Only shares deposited before the proposal should count");
+    }
+    const waitMs = (votingEnd.toNumber() + 1) * 1000 - Date.now();
+    await new Promise((resolve) => setTimeout(resolve, Math.max(waitMs, 0)));
+
+    await program.methods.finalizeProposal().accounts({ governance, proposal }).rpc();
+    await program.methods.queueProposal().accounts({ governance, proposal }).rpc();
+    await program.methods
+      .executeProposal()
+      .accounts({
+        governance,
+        proposal,
+        insurancePool: poolAccountKp.publicKey,
+        product,
+        newProduct: null,
+        executor: program.provider.publicKey,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const proposalAccount = await program.account.proposal.fetch(proposal);
+    if (!("executed" in proposalAccount.status)) throw new Error("Proposal should be marked executed");
+    const productAccount = await program.account.product.fetch(product);
+    if (productAccount.annualRateBps !== 300) throw new Error("Proposal should update the premium rate");
   });
 });
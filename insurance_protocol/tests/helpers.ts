@@ -0,0 +1,192 @@
+import * as anchor from "@coral-xyz/anchor";
+import BN from "bn.js";
+import * as web3 from "@solana/web3.js";
+import * as splToken from "@solana/spl-token";
+import type { InsuranceProtocol } from "../target/types/insurance_protocol";
+
+// Fixtures shared by the test suites that build their own pools
+anchor.setProvider(anchor.AnchorProvider.env());
+
+export const program = anchor.workspace.InsuranceProtocol as anchor.Program<InsuranceProtocol>;
+export const payer = (program.provider as anchor.AnchorProvider).wallet as anchor.Wallet;
+export const connection = program.provider.connection;
+export const user = program.provider.publicKey;
+
+export const pda = (seeds: Buffer[]) => web3.PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+export const poolAuthority = pda([Buffer.from("insurance_pool")]);
+
+// Fails unless the transaction is rejected with the given program error
+export const expectError = async (tx: Promise<unknown>, code: string) => {
+  try {
+    await tx;
+  } catch (err) {
+    if (err instanceof anchor.AnchorError && err.error.errorCode.code === code) return;
+    throw err;
+  }
+  throw new Error(`Expected the transaction to fail with ${code}`);
+};
+
+// A policy buyer with the accounts a purchase needs
+export type Holder = {
+  user: web3.PublicKey;
+  signers: web3.Keypair[];
+  userProfile: web3.PublicKey;
+  productHolding: web3.PublicKey;
+  userTokenAccount: web3.PublicKey;
+};
+
+// The fixture doubles as the provider wallet's holder
+export type PoolFixture = Holder & {
+  pool: web3.PublicKey;
+  mint: web3.PublicKey;
+  vault: web3.PublicKey;
+  product: web3.PublicKey;
+};
+
+// Builds a funded pool with one product; terms are counted in seconds
+export const setupPool = async (opts: {
+  termOptions: number[];
+  gracePeriod?: number;
+  maxInstallments?: number;
+  renewalWindow?: number;
+  maxActivePolicies?: number;
+}): Promise<PoolFixture> => {
+  const poolKp = new web3.Keypair();
+  const pool = poolKp.publicKey;
+  const mint = await splToken.createMint(connection, payer.payer, user, null, 6);
+  const vault = pda([Buffer.from("vault"), pool.toBuffer(), mint.toBuffer()]);
+  const lpMint = pda([Buffer.from("lp_mint"), pool.toBuffer()]);
+  const shareEscrow = pda([Buffer.from("share_escrow"), pool.toBuffer()]);
+
+  await program.methods
+    .initializePool(new BN(0), new BN(1), new BN(365 * 86_400), new BN(opts.gracePeriod ?? 0), new BN(0))
+    .accounts({
+      insurancePool: pool,
+      poolAuthority,
+      mint,
+      vault,
+      lpMint,
+      shareEscrow,
+      admin: user,
+      tokenProgram: splToken.TOKEN_PROGRAM_ID,
+      systemProgram: web3.SystemProgram.programId,
+    })
+    .signers([poolKp])
+    .rpc();
+
+  const product = pda([Buffer.from("product"), pool.toBuffer(), new BN(0).toArrayLike(Buffer, "le", 8)]);
+  await program.methods
+    .createProduct({
+      name: "Test cover",
+      termOptions: opts.termOptions.map((term) => new BN(term)),
+      annualRateBps: 200,
+      minDeductible: new BN(0),
+      coinsuranceBps: 0,
+      maxCoveragePerPolicy: new BN(10_000),
+      capacity: new BN(100_000),
+      enabled: true,
+      maxInstallments: opts.maxInstallments ?? 1,
+      renewalWindow: new BN(opts.renewalWindow ?? 0),
+      maxActivePolicies: opts.maxActivePolicies ?? 5,
+    })
+    .accounts({ product, insurancePool: pool, admin: user, systemProgram: web3.SystemProgram.programId })
+    .rpc();
+
+  const userTokenAccount = await splToken.createAccount(
+    connection, payer.payer, mint, user, web3.Keypair.generate()
+  );
+  const userShareAccount = await splToken.createAccount(
+    connection, payer.payer, lpMint, user, web3.Keypair.generate()
+  );
+  await splToken.mintTo(connection, payer.payer, mint, userTokenAccount, payer.payer, 100_000);
+  await program.methods
+    .stakeIntoPool(new BN(50_000))
+    .accounts({
+      user,
+      userTokenAccount,
+      userShareAccount,
+      vault,
+      lpMint,
+      shareEscrow,
+      poolAuthority,
+      insurancePool: pool,
+      tokenProgram: splToken.TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+
+  const userProfile = pda([Buffer.from("user_profile"), pool.toBuffer(), user.toBuffer()]);
+  await program.methods
+    .initializeUserProfile()
+    .accounts({ userProfile, insurancePool: pool, user, systemProgram: web3.SystemProgram.programId })
+    .rpc();
+  const productHolding = pda([Buffer.from("product_holding"), product.toBuffer(), user.toBuffer()]);
+  await program.methods
+    .initializeProductHolding()
+    .accounts({ productHolding, product, user, systemProgram: web3.SystemProgram.programId })
+    .rpc();
+
+  return { pool, mint, vault, product, user, signers: [], userProfile, productHolding, userTokenAccount };
+};
+
+// Funds a fresh wallet and opens its profile and holding in the fixture's pool
+export const addHolder = async (fx: PoolFixture): Promise<Holder> => {
+  const owner = web3.Keypair.generate();
+  await connection.confirmTransaction(await connection.requestAirdrop(owner.publicKey, web3.LAMPORTS_PER_SOL));
+  const userTokenAccount = await splToken.createAccount(
+    connection, payer.payer, fx.mint, owner.publicKey, web3.Keypair.generate()
+  );
+  await splToken.mintTo(connection, payer.payer, fx.mint, userTokenAccount, payer.payer, 10_000);
+
+  const userProfile = pda([Buffer.from("user_profile"), fx.pool.toBuffer(), owner.publicKey.toBuffer()]);
+  await program.methods
+    .initializeUserProfile()
+    .accounts({
+      userProfile,
+      insurancePool: fx.pool,
+      user: owner.publicKey,
+      systemProgram: web3.SystemProgram.programId,
+    })
+    .signers([owner])
+    .rpc();
+  const productHolding = pda([Buffer.from("product_holding"), fx.product.toBuffer(), owner.publicKey.toBuffer()]);
+  await program.methods
+    .initializeProductHolding()
+    .accounts({
+      productHolding,
+      product: fx.product,
+      user: owner.publicKey,
+      systemProgram: web3.SystemProgram.programId,
+    })
+    .signers([owner])
+    .rpc();
+
+  return { user: owner.publicKey, signers: [owner], userProfile, productHolding, userTokenAccount };
+};
+
+// Buys the holder's next policy from the fixture's product
+export const purchase = async (fx: PoolFixture, term: number, installments = 1, holder: Holder = fx) => {
+  const profile = await program.account.userProfile.fetch(holder.userProfile);
+  const policy = pda([
+    Buffer.from("policy"),
+    fx.pool.toBuffer(),
+    holder.user.toBuffer(),
+    profile.policyCount.toArrayLike(Buffer, "le", 8),
+  ]);
+  await program.methods
+    .purchaseInsurance(new BN(1_000), new BN(5_000), new BN(0), new BN(5_000), new BN(term), null, installments)
+    .accounts({
+      user: holder.user,
+      userProfile: holder.userProfile,
+      insurancePolicy: policy,
+      product: fx.product,
+      productHolding: holder.productHolding,
+      insurancePool: fx.pool,
+      userTokenAccount: holder.userTokenAccount,
+      vault: fx.vault,
+      tokenProgram: splToken.TOKEN_PROGRAM_ID,
+      systemProgram: web3.SystemProgram.programId,
+    })
+    .signers(holder.signers)
+    .rpc();
+  return policy;
+};
@@ -0,0 +1,256 @@
+import BN from "bn.js";
+import * as web3 from "@solana/web3.js";
+import * as splToken from "@solana/spl-token";
+import {
+  PoolFixture,
+  addHolder,
+  connection,
+  expectError,
+  payer,
+  pda,
+  poolAuthority,
+  program,
+  purchase,
+  setupPool,
+  user,
+} from "./helpers";
+
+// Policy lifecycle rules that depend on timing or pool limits: pricing of short
+// terms, coverage changes, lapses, renewals and the pause flags. Each test builds
+// its own pool so the terms can be a few seconds long.
+describe("Policy Lifecycle", () => {
+  const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+  // Waits until the cluster clock reaches the given unix time
+  const waitUntil = async (time: number) => {
+    while ((await connection.getBlockTime(await connection.getSlot())) < time) await sleep(250);
+  };
+
+  // Files a claim against the policy and, when asked, has the claims assessor pay it in full
+  const fileClaim = async (fx: PoolFixture, policy: web3.PublicKey, amount: number, approve: boolean) => {
+    const { claimCount } = await program.account.insurancePolicy.fetch(policy);
+    const claim = pda([Buffer.from("claim"), policy.toBuffer(), claimCount.toArrayLike(Buffer, "le", 8)]);
+    await program.methods
+      .submitClaim(new BN(amount), Array(32).fill(0), "ipfs://claim-evidence")
+      .accounts({
+        insurancePolicy: policy,
+        insurancePool: fx.pool,
+        claim,
+        user,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    if (!approve) return;
+    await program.methods
+      .approveClaim(new BN(amount))
+      .accounts({
+        claim,
+        insurancePolicy: policy,
+        product: fx.product,
+        productHolding: fx.productHolding,
+        policyholderTokenAccount: fx.userTokenAccount,
+        insurancePool: fx.pool,
+        vault: fx.vault,
+        poolAuthority,
+        claimsAssessor: user,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+  };
+
+  const cancel = (fx: PoolFixture, policy: web3.PublicKey) =>
+    program.methods
+      .cancelPolicy()
+      .accounts({
+        insurancePolicy: policy,
+        product: fx.product,
+        productHolding: fx.productHolding,
+        insurancePool: fx.pool,
+        vault: fx.vault,
+        poolAuthority,
+        userTokenAccount: fx.userTokenAccount,
+        user,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+  it("Charges short terms and only lets coverage go down", async () => {
+    const fx = await setupPool({ termOptions: [3_600] });
+    const policy = await purchase(fx, 3_600);
+
+    // 5_000 of cover for an hour at 2% a year is a fraction of a token
+    const bought = await program.account.insurancePolicy.fetch(policy);
+    if (bought.premiumAmount.toNumber() !== 1) throw new Error("Premium should round up to the smallest unit");
+
+    await expectError(
+      program.methods
+        .adjustCoverage(new BN(6_000))
+        .accounts({ insurancePolicy: policy, product: fx.product, insurancePool: fx.pool, user })
+        .rpc(),
+      "CoverageIncreaseNotAllowed"
+    );
+
+    await program.methods
+      .adjustCoverage(new BN(4_000))
+      .accounts({ insurancePolicy: policy, product: fx.product, insurancePool: fx.pool, user })
+      .rpc();
+    const product = await program.account.product.fetch(fx.product);
+    if (product.activeCoverage.toNumber() !== 4_000) throw new Error("Reduced cover should release product capacity");
+  });
+
+  it("Caps the policies each user holds under a product", async () => {
+    const fx = await setupPool({ termOptions: [3_600], maxActivePolicies: 1 });
+    const first = await purchase(fx, 3_600);
+    await expectError(purchase(fx, 3_600), "TooManyActivePolicies");
+
+    // The cap is per user, so other buyers are not locked out
+    const other = await addHolder(fx);
+    await purchase(fx, 3_600, 1, other);
+
+    // Closing a policy frees its slot
+    await cancel(fx, first);
+    await purchase(fx, 3_600);
+    const holding = await program.account.productHolding.fetch(fx.productHolding);
+    if (holding.activePolicies !== 1) throw new Error("Holding should count the user's active policies");
+  });
+
+  it("Holds cancellation refunds while claim payouts are paused", async () => {
+    const fx = await setupPool({ termOptions: [3_600] });
+    const policy = await purchase(fx, 3_600);
+
+    await program.methods.setPauseFlags(1 << 4).accounts({ insurancePool: fx.pool, pauser: user }).rpc();
+    await expectError(cancel(fx, policy), "Paused");
+
+    await program.methods.setPauseFlags(0).accounts({ insurancePool: fx.pool, pauser: user }).rpc();
+    await cancel(fx, policy);
+    const canceled = await program.account.insurancePolicy.fetch(policy);
+    if (canceled.isActive) throw new Error("Policy should cancel once payouts resume");
+  });
+
+  it("Counts earlier payouts towards the large claim threshold", async () => {
+    const fx = await setupPool({ termOptions: [3_600] });
+    const policy = await purchase(fx, 3_600);
+    const multisigSigner = web3.Keypair.generate().publicKey;
+    await program.methods
+      .setLargeClaimApproval(multisigSigner, new BN(1_000))
+      .accounts({ insurancePool: fx.pool, admin: user })
+      .rpc();
+
+    await fileClaim(fx, policy, 600, true);
+    // A second 600 brings the policy's payouts to 1_200, above the threshold
+    await expectError(fileClaim(fx, policy, 600, true), "MultisigApprovalRequired");
+  });
+
+  it("Rejects more installments than the term has seconds", async () => {
+    const fx = await setupPool({ termOptions: [1], maxInstallments: 2 });
+    await expectError(purchase(fx, 1, 2), "InvalidPolicyTerms");
+  });
+
+  it("Lapses a policy once an installment is overdue past the grace period", async () => {
+    const fx = await setupPool({ termOptions: [4], gracePeriod: 0, maxInstallments: 2 });
+    // Two installments over four seconds: the first pays cover for the first two seconds
+    const policy = await purchase(fx, 4, 2);
+    const lapse = () =>
+      program.methods
+        .lapsePolicy()
+        .accounts({
+          insurancePolicy: policy,
+          product: fx.product,
+          productHolding: fx.productHolding,
+          insurancePool: fx.pool,
+        })
+        .rpc();
+
+    await expectError(lapse(), "PolicyNotOverdue");
+
+    await sleep(3_500);
+    await lapse();
+
+    const lapsed = await program.account.insurancePolicy.fetch(policy);
+    if (!lapsed.lapsed || lapsed.isActive) throw new Error("Overdue policy should lapse");
+    const product = await program.account.product.fetch(fx.product);
+    if (product.activeCoverage.toNumber() !== 0) throw new Error("Lapsed cover should release product capacity");
+  });
+
+  it("Renews once per term and reinstates coverage when the new term starts", async () => {
+    const fx = await setupPool({ termOptions: [12], renewalWindow: 8 });
+    const policy = await purchase(fx, 12);
+    const { startTime, endTime } = await program.account.insurancePolicy.fetch(policy);
+    await fileClaim(fx, policy, 1_000, true);
+
+    const renew = () =>
+      program.methods
+        .renewPolicy(new BN(1_000))
+        .accounts({
+          insurancePolicy: policy,
+          product: fx.product,
+          insurancePool: fx.pool,
+          userTokenAccount: fx.userTokenAccount,
+          vault: fx.vault,
+          user,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    await waitUntil(endTime.toNumber() - 7);
+    await renew();
+    const renewed = await program.account.insurancePolicy.fetch(policy);
+    if (!renewed.endTime.eq(endTime.addn(12))) throw new Error("Renewal should extend the term");
+    if (!renewed.startTime.eq(startTime)) throw new Error("The current term should keep running");
+    if (renewed.claimedToDate.toNumber() !== 1_000) throw new Error("Used cover should stay used until the new term");
+
+    await expectError(renew(), "AlreadyRenewed");
+
+    // Cover reinstated for the next term stays reserved, including payouts made before it starts
+    await fileClaim(fx, policy, 500, true);
+    let product = await program.account.product.fetch(fx.product);
+    if (product.activeCoverage.toNumber() !== 5_000) throw new Error("Renewed cover should stay reserved");
+
+    // The first claim in the new term sees the full limit again
+    await waitUntil(endTime.toNumber() + 1);
+    await fileClaim(fx, policy, 500, false);
+    const rolled = await program.account.insurancePolicy.fetch(policy);
+    if (!rolled.claimedToDate.isZero()) throw new Error("The new term should reinstate the coverage limit");
+    if (rolled.claimCount.toNumber() !== 3) throw new Error("Claim history should carry over");
+    product = await program.account.product.fetch(fx.product);
+    if (product.activeCoverage.toNumber() !== 5_000) throw new Error("Reinstated cover should stay reserved");
+  });
+
+  it("Auto-renews through the delegate once per term", async () => {
+    const fx = await setupPool({ termOptions: [6], renewalWindow: 3 });
+    const policy = await purchase(fx, 6);
+    const { endTime } = await program.account.insurancePolicy.fetch(policy);
+
+    await program.methods.setAutoRenew(true, new BN(1_000)).accounts({ insurancePolicy: policy, user }).rpc();
+    await splToken.approve(connection, payer.payer, fx.userTokenAccount, poolAuthority, payer.payer, 1_000);
+
+    const crank = () =>
+      program.methods
+        .processAutoRenewal()
+        .accounts({
+          insurancePolicy: policy,
+          product: fx.product,
+          insurancePool: fx.pool,
+          userTokenAccount: fx.userTokenAccount,
+          vault: fx.vault,
+          poolAuthority,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    await expectError(crank(), "OutsideRenewalWindow");
+
+    await waitUntil(endTime.toNumber() - 2);
+    await crank();
+    const renewed = await program.account.insurancePolicy.fetch(policy);
+    if (!renewed.endTime.eq(endTime.addn(6)) || renewed.renewalCount !== 1) {
+      throw new Error("Crank should renew the policy for one more term");
+    }
+    const account = await splToken.getAccount(connection, fx.userTokenAccount);
+    const charged = BigInt(1_000) - account.delegatedAmount;
+    if (charged !== BigInt(renewed.premiumAmount.toNumber())) throw new Error("Crank should charge the renewal premium");
+
+    // A second crank in the same term must not charge the holder again
+    await expectError(crank(), "AlreadyRenewed");
+  });
+});
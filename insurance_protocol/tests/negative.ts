@@ -0,0 +1,202 @@
+import BN from "bn.js";
+import * as web3 from "@solana/web3.js";
+import * as splToken from "@solana/spl-token";
+import {
+  PoolFixture,
+  connection,
+  expectError,
+  payer,
+  pda,
+  poolAuthority,
+  program,
+  purchase,
+  setupPool,
+  user,
+} from "./helpers";
+
+// Every instruction must reject accounts that do not belong together: a policy
+// from another pool, a product from another pool, a signer that does not
+// own the policy, or a signer without the required pool role.
+describe("Account Relationship Checks", () => {
+  const stranger = web3.Keypair.generate();
+  const term = 90 * 86_400;
+
+  let poolA: PoolFixture & { policy: web3.PublicKey };
+  let poolB: PoolFixture & { policy: web3.PublicKey };
+
+  // Builds a funded pool with one product and one policy held by the provider wallet
+  const setupPoolWithPolicy = async () => {
+    const fx = await setupPool({ termOptions: [term] });
+    return { ...fx, policy: await purchase(fx, term) };
+  };
+
+  before(async () => {
+    poolA = await setupPoolWithPolicy();
+    poolB = await setupPoolWithPolicy();
+  });
+
+  it("Rejects a policy paired with another pool", async () => {
+    await expectError(
+      program.methods
+        .adjustCoverage(new BN(4_000))
+        .accounts({ insurancePolicy: poolA.policy, product: poolA.product, insurancePool: poolB.pool, user })
+        .rpc(),
+      "PolicyPoolMismatch"
+    );
+
+    await expectError(
+      program.methods
+        .payPremiumWithToken()
+        .accounts({
+          insurancePolicy: poolA.policy,
+          user,
+          userTokenAccount: poolB.userTokenAccount,
+          vault: poolB.vault,
+          insurancePool: poolB.pool,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        })
+        .rpc(),
+      "PolicyPoolMismatch"
+    );
+  });
+
+  it("Rejects a claim approval routed through another pool", async () => {
+    const claim = pda([Buffer.from("claim"), poolA.policy.toBuffer(), new BN(0).toArrayLike(Buffer, "le", 8)]);
+    await program.methods
+      .submitClaim(new BN(500), Array(32).fill(0), "ipfs://claim-evidence")
+      .accounts({
+        insurancePolicy: poolA.policy,
+        insurancePool: poolA.pool,
+        claim,
+        user,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    await expectError(
+      program.methods
+        .approveClaim(new BN(500))
+        .accounts({
+          claim,
+          insurancePolicy: poolA.policy,
+          product: poolA.product,
+          productHolding: poolA.productHolding,
+          policyholderTokenAccount: poolB.userTokenAccount,
+          insurancePool: poolB.pool,
+          vault: poolB.vault,
+          poolAuthority,
+          claimsAssessor: user,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        })
+        .rpc(),
+      "PolicyPoolMismatch"
+    );
+
+    await expectError(
+      program.methods
+        .rejectClaim()
+        .accounts({ claim, insurancePolicy: poolA.policy, insurancePool: poolB.pool, claimsAssessor: user })
+        .rpc(),
+      "PolicyPoolMismatch"
+    );
+  });
+
+  it("Rejects a product from another pool", async () => {
+    const profile = await program.account.userProfile.fetch(poolA.userProfile);
+    await expectError(
+      program.methods
+        .purchaseInsurance(new BN(100), new BN(1_000), new BN(100), new BN(1_000), new BN(term), null, 1)
+        .accounts({
+          user,
+          userProfile: poolA.userProfile,
+          insurancePolicy: pda([
+            Buffer.from("policy"),
+            poolA.pool.toBuffer(),
+            user.toBuffer(),
+            profile.policyCount.toArrayLike(Buffer, "le", 8),
+          ]),
+          product: poolB.product,
+          productHolding: poolB.productHolding,
+          insurancePool: poolA.pool,
+          userTokenAccount: poolA.userTokenAccount,
+          vault: poolA.vault,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc(),
+      "InvalidProduct"
+    );
+  });
+
+  it("Rejects a signer without the pool role", async () => {
+    const strangerTokenAccount = await splToken.createAccount(
+      connection, payer.payer, poolA.mint, stranger.publicKey, web3.Keypair.generate()
+    );
+
+    // Fund the stranger so the request account can be created before the role check fails
+    await connection.confirmTransaction(await connection.requestAirdrop(stranger.publicKey, web3.LAMPORTS_PER_SOL));
+
+    await expectError(
+      program.methods
+        .queueWithdrawal(new BN(1), null)
+        .accounts({
+          withdrawalRequest: pda([Buffer.from("withdrawal"), poolA.pool.toBuffer(), new BN(0).toArrayLike(Buffer, "le", 8)]),
+          insurancePool: poolA.pool,
+          destination: strangerTokenAccount,
+          treasurer: stranger.publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([stranger])
+        .rpc(),
+      "Unauthorized"
+    );
+
+    await expectError(
+      program.methods
+        .grantRole({ claimsAssessor: {} }, stranger.publicKey)
+        .accounts({ insurancePool: poolA.pool, admin: stranger.publicKey })
+        .signers([stranger])
+        .rpc(),
+      "Unauthorized"
+    );
+  });
+
+  it("Rejects a signer that does not own the policy", async () => {
+    const strangerTokenAccount = await splToken.createAccount(
+      connection, payer.payer, poolA.mint, stranger.publicKey, web3.Keypair.generate()
+    );
+
+    await expectError(
+      program.methods
+        .cancelPolicy()
+        .accounts({
+          insurancePolicy: poolA.policy,
+          product: poolA.product,
+          productHolding: poolA.productHolding,
+          insurancePool: poolA.pool,
+          vault: poolA.vault,
+          poolAuthority,
+          userTokenAccount: strangerTokenAccount,
+          user: stranger.publicKey,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        })
+        .signers([stranger])
+        .rpc(),
+      "NotPolicyOwner"
+    );
+
+    await expectError(
+      program.methods
+        .adjustCoverage(new BN(4_000))
+        .accounts({
+          insurancePolicy: poolA.policy,
+          product: poolA.product,
+          insurancePool: poolA.pool,
+          user: stranger.publicKey,
+        })
+        .signers([stranger])
+        .rpc(),
+      "NotPolicyOwner"
+    );
+  });
+});